@@ -1,48 +1,151 @@
 //! Activate or deactivate digital audio interface
-#![allow(clippy::new_without_default)]
+//!
+//! The builder track in its type whether the interface is activated, so code requiring an active
+//! interface can take an `ActiveControl<Active>`.
+//!
+//! ## Example
+//! ```
+//! # use wm8731_alt::command::active_control::*;
+//! fn start(cmd: ActiveControl<Active>) {}
+//! start(active_control().active());
+//! ```
+//!
+//! ## Example of bad usage
+//! Following example show incorrect usage and should not compile.
+//! ```compile_fail
+//! # use wm8731_alt::command::active_control::*;
+//! fn start(cmd: ActiveControl<Active>) {}
+//! //error, the interface is deactivated
+//! start(active_control().active().inactive());
+//! ```
 
+use crate::interface::Frame;
+use crate::registers;
 use crate::Command;
 use core::marker::PhantomData;
 
-/// Power down configuration builder.
-#[derive(Debug, Eq, PartialEq)]
-pub struct ActiveControl {
+/// Marker used to indicate the digital audio interface is activated.
+pub struct Active;
+/// Marker used to indicate the digital audio interface is deactivated.
+pub struct Inactive;
+/// Marker used to indicate the digital audio interface state isn't tracked.
+pub struct AnyActive;
+
+/// Active control configuration builder.
+#[derive(Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ActiveControl<STATE = AnyActive> {
     data: u16,
+    state: PhantomData<STATE>,
+}
+
+impl<STATE> core::fmt::Debug for ActiveControl<STATE> {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
 }
 
-impl Copy for ActiveControl {}
+impl<STATE> Copy for ActiveControl<STATE> {}
 
-impl Clone for ActiveControl {
+impl<STATE> Clone for ActiveControl<STATE> {
     fn clone(&self) -> Self {
         *self
     }
 }
 
-/// Instanciate a builder for power down configuration.
-pub fn active_control() -> ActiveControl {
+impl<STATE> From<ActiveControl<STATE>> for Frame {
+    fn from(builder: ActiveControl<STATE>) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for ActiveControl<Inactive> {
+    fn default() -> Self {
+        ActiveControl::new()
+    }
+}
+
+/// Instanciate a builder for active control configuration, with the interface deactivated.
+pub const fn active_control() -> ActiveControl<Inactive> {
     ActiveControl::new()
 }
 
-impl ActiveControl {
-    fn new() -> Self {
-        Self { data: 0b1001 << 9 }
+impl ActiveControl<Inactive> {
+    const fn new() -> Self {
+        Self {
+            data: (registers::ACTIVE_CONTROL as u16) << 9 | registers::ACTIVE_CONTROL_DEFAULT,
+            state: PhantomData::<Inactive>,
+        }
+    }
+}
+
+impl ActiveControl<AnyActive> {
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    ///
+    ///The interface state isn't tracked by the resulting builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        Self::with_data((registers::ACTIVE_CONTROL as u16) << 9 | cmd.value())
+    }
+}
+
+impl<STATE> ActiveControl<STATE> {
+    const fn with_data<S>(data: u16) -> ActiveControl<S> {
+        ActiveControl::<S> {
+            data,
+            state: PhantomData::<S>,
+        }
     }
     ///Activate digital audio interface
     #[must_use]
-    pub fn active(mut self) -> ActiveControl {
-        self.data |= 0b1;
-        self
+    pub const fn active(self) -> ActiveControl<Active> {
+        Self::with_data(self.data | 0b1)
     }
     ///Deactivate digital audio interface
     #[must_use]
-    pub fn inactive(mut self) -> ActiveControl {
-        self.data &= !(0b1);
-        self
+    pub const fn inactive(self) -> ActiveControl<Inactive> {
+        Self::with_data(self.data & !(0b1))
     }
-    pub fn into_command(self) -> Command<()> {
+    pub const fn into_command(self) -> Command<()> {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
         }
     }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()> {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn typed_state() {
+        let active: ActiveControl<Active> = active_control().active();
+        let test = active.into_command().data;
+        let expected = 0b1001 << 9 | 0b1;
+        assert!(
+            test == expected,
+            "Got {:#b}, expected {:#b}",
+            test,
+            expected
+        );
+        let inactive: ActiveControl<Inactive> = active.inactive();
+        let test = inactive.into_command().data;
+        let expected = 0b1001 << 9;
+        assert!(
+            test == expected,
+            "Got {:#b}, expected {:#b}",
+            test,
+            expected
+        );
+    }
 }
@@ -1,6 +1,7 @@
 //! Analogue Audio Path configuration
-#![allow(clippy::new_without_default)]
 
+use crate::interface::Frame;
+use crate::registers;
 use crate::Command;
 use core::marker::PhantomData;
 
@@ -9,11 +10,19 @@ mod side_att_db;
 pub use side_att_db::*;
 
 /// Analogue audio path configuration builder.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AnalogueAudioPath {
     data: u16,
 }
 
+impl core::fmt::Debug for AnalogueAudioPath {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
+
 impl Copy for AnalogueAudioPath {}
 
 impl Clone for AnalogueAudioPath {
@@ -22,39 +31,115 @@ impl Clone for AnalogueAudioPath {
     }
 }
 
+impl From<AnalogueAudioPath> for Frame {
+    fn from(builder: AnalogueAudioPath) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for AnalogueAudioPath {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Instanciate a builder for Analogue audio path configuration.
-pub fn analogue_audio_path() -> AnalogueAudioPath {
+pub const fn analogue_audio_path() -> AnalogueAudioPath {
     AnalogueAudioPath::new()
 }
 
 impl AnalogueAudioPath {
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
-            data: 0b100 << 9 | 0b1010,
+            data: (registers::ANALOGUE_AUDIO_PATH as u16) << 9
+                | registers::ANALOGUE_AUDIO_PATH_DEFAULT,
         }
     }
-    pub fn micboost(self) -> Micboost {
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value();
+        new
+    }
+    ///Route the line input straight to the outputs, without the DAC.
+    ///
+    ///Sets INSEL to line, BYPASS enabled, DACSEL deselected and SIDETONE disabled. Other fields
+    ///are kept, write BYPASS and DACSEL separately to mix both sources.
+    #[must_use]
+    pub const fn line_bypass(self) -> Self {
+        self.insel()
+            .line()
+            .bypass()
+            .enable()
+            .dacsel()
+            .deselect()
+            .sidetone()
+            .disable()
+    }
+    ///Route the DAC to the outputs, without analog bypass.
+    ///
+    ///Sets DACSEL selected, BYPASS disabled and SIDETONE disabled. Other fields are kept, INSEL
+    ///included so the ADC input is unchanged.
+    #[must_use]
+    pub const fn dac_playback(self) -> Self {
+        self.dacsel()
+            .select()
+            .bypass()
+            .disable()
+            .sidetone()
+            .disable()
+    }
+    ///Use the microphone as ADC input.
+    ///
+    ///Sets INSEL to microphone, MICBOOST to `boost` and MUTEMIC disabled, so the mic is never
+    ///left selected but muted. Other fields are kept.
+    #[must_use]
+    pub const fn mic_input(self, boost: bool) -> Self {
+        self.insel()
+            .microphone()
+            .micboost()
+            .bit(boost)
+            .mutemic()
+            .disable()
+    }
+    ///Mute the microphone input, keeping INSEL and MICBOOST so [`Self::mic_input`] settings are
+    ///retrieved once unmuted.
+    #[must_use]
+    pub const fn mic_muted(self) -> Self {
+        self.mutemic().enable()
+    }
+    pub const fn micboost(self) -> Micboost {
         Micboost { cmd: self }
     }
-    pub fn mutemic(self) -> Mutemic {
+    pub const fn mutemic(self) -> Mutemic {
         Mutemic { cmd: self }
     }
-    pub fn insel(self) -> Insel {
+    pub const fn insel(self) -> Insel {
         Insel { cmd: self }
     }
-    pub fn bypass(self) -> Bypass {
+    pub const fn bypass(self) -> Bypass {
         Bypass { cmd: self }
     }
-    pub fn dacsel(self) -> Dacsel {
+    pub const fn dacsel(self) -> Dacsel {
         Dacsel { cmd: self }
     }
-    pub fn sidetone(self) -> Sidetone {
+    pub const fn sidetone(self) -> Sidetone {
         Sidetone { cmd: self }
     }
-    pub fn sideatt(self) -> Sideatt {
+    pub const fn sideatt(self) -> Sideatt {
         Sideatt { cmd: self }
     }
-    pub fn into_command(self) -> Command<()> {
+    pub const fn into_command(self) -> Command<()> {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()> {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
@@ -67,11 +152,67 @@ impl_toggle_writer!(Mutemic, AnalogueAudioPath, 1);
 impl_toggle_writer!(Bypass, AnalogueAudioPath, 3);
 impl_toggle_writer!(Sidetone, AnalogueAudioPath, 5);
 
+///Microphone input boost values.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MicboostV {
+    ///No boost.
+    Disabled,
+    ///Fixed +20dB boost.
+    Boost20dB,
+}
+
+impl MicboostV {
+    ///Return the MICBOOST bit value.
+    pub const fn as_bit(self) -> bool {
+        matches!(self, MicboostV::Boost20dB)
+    }
+}
+
+impl From<bool> for MicboostV {
+    ///Convert from the MICBOOST bit value.
+    fn from(bit: bool) -> Self {
+        if bit {
+            MicboostV::Boost20dB
+        } else {
+            MicboostV::Disabled
+        }
+    }
+}
+
+impl Micboost {
+    ///Set the microphone input boost, that is a fixed +20dB gain when enabled.
+    #[must_use]
+    pub const fn variant(self, value: MicboostV) -> AnalogueAudioPath {
+        self.bit(value.as_bit())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum InselV {
     Line,
     Microphone,
 }
 
+impl InselV {
+    ///Return the INSEL bit value.
+    pub const fn as_bit(self) -> bool {
+        matches!(self, InselV::Microphone)
+    }
+}
+
+impl From<bool> for InselV {
+    ///Convert from the INSEL bit value.
+    fn from(bit: bool) -> Self {
+        if bit {
+            InselV::Microphone
+        } else {
+            InselV::Line
+        }
+    }
+}
+
 pub struct Insel {
     cmd: AnalogueAudioPath,
 }
@@ -84,7 +225,7 @@ impl Insel {
     impl_clear_bit!(line, AnalogueAudioPath, 2);
 
     #[must_use]
-    pub fn variant(self, value: InselV) -> AnalogueAudioPath {
+    pub const fn variant(self, value: InselV) -> AnalogueAudioPath {
         match value {
             InselV::Microphone => self.microphone(),
             InselV::Line => self.line(),
@@ -92,11 +233,31 @@ impl Insel {
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DacselV {
     Deselect,
     Select,
 }
 
+impl DacselV {
+    ///Return the DACSEL bit value.
+    pub const fn as_bit(self) -> bool {
+        matches!(self, DacselV::Select)
+    }
+}
+
+impl From<bool> for DacselV {
+    ///Convert from the DACSEL bit value.
+    fn from(bit: bool) -> Self {
+        if bit {
+            DacselV::Select
+        } else {
+            DacselV::Deselect
+        }
+    }
+}
+
 pub struct Dacsel {
     cmd: AnalogueAudioPath,
 }
@@ -109,7 +270,7 @@ impl Dacsel {
     impl_clear_bit!(deselect, AnalogueAudioPath, 4);
 
     #[must_use]
-    pub fn variant(self, value: DacselV) -> AnalogueAudioPath {
+    pub const fn variant(self, value: DacselV) -> AnalogueAudioPath {
         match value {
             DacselV::Deselect => self.deselect(),
             DacselV::Select => self.select(),
@@ -117,6 +278,20 @@ impl Dacsel {
     }
 }
 
+///Sidetone attenuation values.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SideAttV {
+    ///-6dB
+    N6dB = 0b00,
+    ///-9dB
+    N9dB = 0b01,
+    ///-12dB
+    N12dB = 0b10,
+    ///-15dB
+    N15dB = 0b11,
+}
+
 ///Control attenuation of the mic input when directly connected to ouput.
 pub struct Sideatt {
     cmd: AnalogueAudioPath,
@@ -125,9 +300,127 @@ pub struct Sideatt {
 impl Sideatt {
     impl_bits!(AnalogueAudioPath, 2, 6);
     ///Set attenuation from a dB representation.
-    pub fn db(mut self, volume: SideAttdB) -> AnalogueAudioPath {
+    pub const fn db(mut self, volume: SideAttdB) -> AnalogueAudioPath {
         let mask = !((!0) << 2) << 6;
-        self.cmd.data = self.cmd.data & !mask | (volume.into_raw() as u16);
+        self.cmd.data = self.cmd.data & !mask | (volume.into_raw() as u16) << 6 & mask;
         self.cmd
     }
+
+    #[must_use]
+    pub const fn variant(self, value: SideAttV) -> AnalogueAudioPath {
+        match value {
+            SideAttV::N6dB => self.bits(0b00),
+            SideAttV::N9dB => self.bits(0b01),
+            SideAttV::N12dB => self.bits(0b10),
+            SideAttV::N15dB => self.bits(0b11),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn routing_presets() {
+        let test = analogue_audio_path().line_bypass().data;
+        let expected = 0b100 << 9 | 0b0_0000_1010;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+        let test = analogue_audio_path()
+            .insel()
+            .microphone()
+            .dac_playback()
+            .data;
+        let expected = 0b100 << 9 | 0b0_0001_0110;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+    }
+    #[test]
+    fn mic_presets() {
+        let test = analogue_audio_path().mic_input(true).data;
+        let expected = 0b100 << 9 | 0b0_0000_1101;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+        let test = analogue_audio_path().mic_input(false).data;
+        let expected = 0b100 << 9 | 0b0_0000_1100;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+        let test = analogue_audio_path().mic_input(true).mic_muted().data;
+        let expected = 0b100 << 9 | 0b0_0000_1111;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+    }
+    #[test]
+    fn dacsel_select() {
+        let cmd = analogue_audio_path().dacsel().select().into_command();
+        let expected = 0b100 << 9 | 0b1_1010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = analogue_audio_path().dacsel().deselect().into_command();
+        let expected = 0b100 << 9 | 0b0_1010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn micboost_variant() {
+        let cmd = analogue_audio_path()
+            .micboost()
+            .variant(MicboostV::Boost20dB)
+            .into_command();
+        let expected = 0b100 << 9 | 0b0_1011;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn sideatt_db() {
+        for raw in 0..=0b11 {
+            let db = SideAttdB::from_raw(raw);
+            let cmd = analogue_audio_path().sideatt().db(db).into_command();
+            let expected = analogue_audio_path().sideatt().bits(raw).into_command();
+            assert!(
+                cmd.data == expected.data,
+                "Got {:#b},expected {:#b}",
+                cmd.data,
+                expected.data
+            );
+        }
+        let cmd = analogue_audio_path().sideatt().db(SideAttdB::N15DB);
+        let expected = 0b100 << 9 | 0b1100_1010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn sideatt_variant() {
+        let cmd = analogue_audio_path().sideatt().variant(SideAttV::N12dB);
+        let expected = 0b100 << 9 | 0b1000_1010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn variant_bit_conversion() {
+        for bit in [false, true].iter() {
+            let test = InselV::from(*bit).as_bit();
+            assert!(test == *bit, "Got {}, expected {}", test, bit);
+            let test = DacselV::from(*bit).as_bit();
+            assert!(test == *bit, "Got {}, expected {}", test, bit);
+        }
+        let test = InselV::from(true);
+        let expected = InselV::Microphone;
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
 }
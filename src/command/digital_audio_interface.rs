@@ -1,60 +1,228 @@
 //! Digital Audio Path configuration
-#![allow(clippy::new_without_default)]
+//!
+//! The builder track the selected format in its type, so format dependent settings only expose
+//! options that are meaningful for this format. For example, `lrp` select DSP mode A or B when the
+//! format is DSP, while it swap the LRC phase with other formats.
+//!
+//! The master/slave mode is tracked the same way, so code receiving a builder can require a given
+//! mode, for example `DigitalAudioInterface<(I2s, Master)>`. Note that BCLKINV and LRP apply to
+//! the clocks whoever drives them, so they are available in both modes.
+//!
+//! ## Example
+//! ```
+//! # use wm8731_alt::command::digital_audio_interface::*;
+//! let cmd = digital_audio_interface()
+//!     .format()
+//!     .dsp()
+//!     .lrp()
+//!     .msb_on_second_bclk()
+//!     .ms()
+//!     .master()
+//!     .into_command();
+//! ```
+//!
+//! ## Example of bad usage
+//! Following examples show incorrect usage and should not compile.
+//! ```compile_fail
+//! # use wm8731_alt::command::digital_audio_interface::*;
+//! //error, DSP mode selection isn't available with I2S format
+//! let cmd = digital_audio_interface().format().i2s().lrp().msb_on_second_bclk();
+//! ```
+//! ```compile_fail
+//! # use wm8731_alt::command::digital_audio_interface::*;
+//! //error, 32 bits word length isn't supported with right justified format
+//! let cmd = digital_audio_interface().format().right_justified().iwl().iwl_32_bits();
+//! ```
+//! ```compile_fail
+//! # use wm8731_alt::command::digital_audio_interface::*;
+//! //error, raw bits aren't available with right justified format, use `try_bits`
+//! let cmd = digital_audio_interface().format().right_justified().iwl().bits(0b11);
+//! ```
+//!
+//! Since the word length isn't tracked in the type, switching to right justified format replaces
+//! a 32 bits word length by 24 bits, the reset value.
 
+use crate::interface::Frame;
+use crate::registers;
 use crate::Command;
 use core::marker::PhantomData;
 
+///Error returned when the format and word length combination isn't supported by the codec.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IncoherentFormat;
+
+pub mod state_marker {
+    //! Markers to track state of the digital audio interface builder.
+
+    /// Marker used to indicate DSP format.
+    pub struct Dsp;
+    /// Marker used to indicate I2S format.
+    pub struct I2s;
+    /// Marker used to indicate left justified format.
+    pub struct LeftJustified;
+    /// Marker used to indicate right justified format.
+    pub struct RightJustified;
+    /// Marker used to indicate a format only known at runtime.
+    ///
+    /// Only raw writes are available for format dependent fields.
+    pub struct AnyFormat;
+    /// Marker used to indicate master mode.
+    pub struct Master;
+    /// Marker used to indicate slave mode.
+    pub struct Slave;
+    /// Marker used to indicate a master/slave mode only known at runtime.
+    pub struct AnyMode;
+}
+
+use state_marker::*;
+
 /// builder for digital audio interface configuration
-#[derive(Debug, Eq, PartialEq)]
-pub struct DigitalAudioInterface {
+///
+/// `T` is a `(FORMAT, MODE)` tuple of markers from [`state_marker`].
+#[derive(Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DigitalAudioInterface<T> {
     data: u16,
+    t: PhantomData<T>,
 }
 
-impl Copy for DigitalAudioInterface {}
+impl<T> core::fmt::Debug for DigitalAudioInterface<T> {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
+
+impl<T> Copy for DigitalAudioInterface<T> {}
 
-impl Clone for DigitalAudioInterface {
+impl<T> Clone for DigitalAudioInterface<T> {
     fn clone(&self) -> Self {
         *self
     }
 }
 
+impl<T> From<DigitalAudioInterface<T>> for Frame {
+    fn from(builder: DigitalAudioInterface<T>) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for DigitalAudioInterface<(I2s, Slave)> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Instanciate a builder for digital audio interface configuration.
-pub fn digital_audio_interface() -> DigitalAudioInterface {
+///
+/// The builder start from the reset value: I2S format, 24 bits word length, normal LRC phase, no
+/// channel swap, slave mode and non inverted BCLK.
+pub const fn digital_audio_interface() -> DigitalAudioInterface<(I2s, Slave)> {
     DigitalAudioInterface::new()
 }
 
-impl DigitalAudioInterface {
-    fn new() -> Self {
+impl DigitalAudioInterface<(I2s, Slave)> {
+    const fn new() -> Self {
+        Self {
+            data: (registers::DIGITAL_AUDIO_INTERFACE as u16) << 9
+                | registers::DIGITAL_AUDIO_INTERFACE_DEFAULT,
+            t: PhantomData::<(I2s, Slave)>,
+        }
+    }
+    ///Preset for an I2S slave interface with the given word length.
+    ///
+    ///Compared to the reset value, it only override the word length. Format is I2S, LRC phase is
+    ///normal, channels aren't swapped, mode is slave and BCLK isn't inverted.
+    pub const fn for_i2s(iwl: IwlV) -> Self {
+        Self::new()
+            .format()
+            .i2s()
+            .lrp()
+            .normal()
+            .lrswap()
+            .disable()
+            .iwl()
+            .variant(iwl)
+    }
+}
+
+impl DigitalAudioInterface<(AnyFormat, AnyMode)> {
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder. Format and mode
+    ///are only known at runtime.
+    pub const fn from_command(cmd: Command<()>) -> Self {
         Self {
-            data: 0b111 << 9 | 0b1010,
+            data: (registers::DIGITAL_AUDIO_INTERFACE as u16) << 9 | cmd.value(),
+            t: PhantomData::<(AnyFormat, AnyMode)>,
         }
     }
-    pub fn format(self) -> Format {
+}
+
+impl<T> DigitalAudioInterface<T> {
+    pub const fn format(self) -> Format<T> {
         Format { cmd: self }
     }
-    pub fn iwl(self) -> Iwl {
+    pub const fn iwl(self) -> Iwl<T> {
         Iwl { cmd: self }
     }
-    pub fn lrp(self) -> Lrp {
+    pub const fn lrp(self) -> Lrp<T> {
         Lrp { cmd: self }
     }
-    pub fn lrswap(self) -> Lrswap {
+    pub const fn lrswap(self) -> Lrswap<T> {
         Lrswap { cmd: self }
     }
-    pub fn ms(self) -> Ms {
+    pub const fn ms(self) -> Ms<T> {
         Ms { cmd: self }
     }
-    pub fn bclkinv(self) -> Bclkinv {
+    pub const fn bclkinv(self) -> Bclkinv<T> {
         Bclkinv { cmd: self }
     }
-    pub fn into_command(self) -> Command<()> {
+    ///Set the DAC channel order, `true` swaps left and right DAC data.
+    ///
+    ///This only writes LRSWAP, which acts on the DAC data of the digital audio interface. ADC data
+    ///aren't swapped, and headphone output and line input registers still address the physical
+    ///left and right channels, so per channel volumes and the `hpboth`/`inboth` bits aren't
+    ///affected by the swap.
+    #[must_use]
+    pub const fn channel_order(self, swapped: bool) -> Self {
+        self.lrswap().bit(swapped)
+    }
+    pub const fn into_command(self) -> Command<()> {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()> {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
         }
     }
+    ///Like [`into_command`](Self::into_command), but fails on a combination the codec doesn't
+    ///support.
+    ///
+    ///This matters for builders whose format is only known at runtime, typed builders already
+    ///forbid such combinations. The only rejected one is 32 bits word length with right justified
+    ///format. LRP is meaningful with every format, it selects DSP mode A or B with DSP format
+    ///and the LRC phase otherwise.
+    pub const fn try_into_command(self) -> Result<Command<()>, IncoherentFormat> {
+        let format = self.data & 0b11;
+        let iwl = (self.data >> 2) & 0b11;
+        if format == 0b00 && iwl == 0b11 {
+            Err(IncoherentFormat)
+        } else {
+            Ok(self.into_command())
+        }
+    }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FormatV {
     Dsp = 0b11,
     I2s = 0b10,
@@ -62,117 +230,408 @@ pub enum FormatV {
     RigthJustified = 0b00,
 }
 
-pub struct Format {
-    cmd: DigitalAudioInterface,
+/// Field writer. Select the audio data format. Change the format marker of the builder.
+pub struct Format<T> {
+    cmd: DigitalAudioInterface<T>,
 }
 
-impl Format {
-    impl_bits!(DigitalAudioInterface, 2, 0);
+impl<FORMAT, MODE> Format<(FORMAT, MODE)> {
+    const fn write<F>(self, value: u8) -> DigitalAudioInterface<(F, MODE)> {
+        let mask = !((!0) << 2);
+        DigitalAudioInterface::<(F, MODE)> {
+            data: self.cmd.data & !mask | (value as u16) & mask,
+            t: PhantomData::<(F, MODE)>,
+        }
+    }
 
+    ///Writes raw bits to the field. The format is then only known at runtime.
     #[must_use]
-    pub fn variant(self, value: FormatV) -> DigitalAudioInterface {
-        match value {
-            FormatV::Dsp => self.bits(0b11),
-            FormatV::I2s => self.bits(0b10),
-            FormatV::LeftJustified => self.bits(0b01),
-            FormatV::RigthJustified => self.bits(0b00),
-        }
+    pub const fn bits(self, value: u8) -> DigitalAudioInterface<(AnyFormat, MODE)> {
+        self.write(value)
+    }
+
+    ///Set the format from a runtime value.
+    #[must_use]
+    pub const fn variant(self, value: FormatV) -> DigitalAudioInterface<(AnyFormat, MODE)> {
+        self.write(value as u8)
     }
 
     #[must_use]
-    pub fn dsp(self) -> DigitalAudioInterface {
-        self.bits(0b11)
+    pub const fn dsp(self) -> DigitalAudioInterface<(Dsp, MODE)> {
+        self.write(0b11)
     }
     #[must_use]
-    pub fn i2s(self) -> DigitalAudioInterface {
-        self.bits(0b10)
+    pub const fn i2s(self) -> DigitalAudioInterface<(I2s, MODE)> {
+        self.write(0b10)
     }
     #[must_use]
-    pub fn left_justified(self) -> DigitalAudioInterface {
-        self.bits(0b01)
+    pub const fn left_justified(self) -> DigitalAudioInterface<(LeftJustified, MODE)> {
+        self.write(0b01)
     }
+    ///Select the right justified format.
+    ///
+    ///32 bits word length isn't supported with this format, so it's replaced by 24 bits, the reset
+    ///value. Other word lengths are kept.
     #[must_use]
-    pub fn right_justified(self) -> DigitalAudioInterface {
-        self.bits(0b00)
+    pub const fn right_justified(self) -> DigitalAudioInterface<(RightJustified, MODE)> {
+        let cmd = self.write::<RightJustified>(0b00);
+        if (cmd.data >> 2) & 0b11 == IwlV::Iwl32bits as u16 {
+            cmd.iwl().iwl_24_bits()
+        } else {
+            cmd
+        }
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum IwlV {
     Iwl32bits = 0b11,
     Iwl24bits = 0b10,
     Iwl20bits = 0b01,
     Iwl16bits = 0b00,
 }
-
-pub struct Iwl {
-    cmd: DigitalAudioInterface,
+/// Field writer. Select the input audio data bit length.
+///
+/// 32 bits isn't available with the right justified format.
+pub struct Iwl<T> {
+    cmd: DigitalAudioInterface<T>,
 }
 
-impl Iwl {
-    impl_bits!(DigitalAudioInterface, 2, 2);
-
+impl<T> Iwl<T> {
+    const fn write(mut self, value: u8) -> DigitalAudioInterface<T> {
+        self.cmd.data = self.cmd.data & !(0b11 << 2) | (value as u16 & 0b11) << 2;
+        self.cmd
+    }
     #[must_use]
-    pub fn variant(self, value: IwlV) -> DigitalAudioInterface {
-        match value {
-            IwlV::Iwl32bits => self.bits(0b11),
-            IwlV::Iwl24bits => self.bits(0b10),
-            IwlV::Iwl20bits => self.bits(0b01),
-            IwlV::Iwl16bits => self.bits(0b00),
-        }
+    pub const fn iwl_24_bits(self) -> DigitalAudioInterface<T> {
+        self.write(0b10)
     }
     #[must_use]
-    pub fn iwl_32_bits(self) -> DigitalAudioInterface {
-        self.bits(0b11)
+    pub const fn iwl_20_bits(self) -> DigitalAudioInterface<T> {
+        self.write(0b01)
     }
     #[must_use]
-    pub fn iwl_24_bits(self) -> DigitalAudioInterface {
-        self.bits(0b10)
+    pub const fn iwl_16_bits(self) -> DigitalAudioInterface<T> {
+        self.write(0b00)
+    }
+}
+
+impl<MODE> Iwl<(RightJustified, MODE)> {
+    ///Writes raw bits to the field, fails if the value doesn't fit in the field or is `0b11`, the
+    ///32 bits word length being unsupported with this format.
+    pub const fn try_bits(
+        self,
+        value: u8,
+    ) -> Result<DigitalAudioInterface<(RightJustified, MODE)>, super::OutOfRange> {
+        if value >= IwlV::Iwl32bits as u8 {
+            Err(super::OutOfRange)
+        } else {
+            Ok(self.write(value))
+        }
     }
+}
+
+macro_rules! impl_iwl_32_bits {
+    ($($format:ty),*) => {
+        $(
+            impl<MODE> Iwl<($format, MODE)> {
+                impl_bits!(DigitalAudioInterface<($format, MODE)>, 2, 2);
+                #[must_use]
+                pub const fn variant(self, value: IwlV) -> DigitalAudioInterface<($format, MODE)> {
+                    self.bits(value as u8)
+                }
+                #[must_use]
+                pub const fn iwl_32_bits(self) -> DigitalAudioInterface<($format, MODE)> {
+                    self.bits(0b11)
+                }
+            }
+        )*
+    };
+}
+
+impl_iwl_32_bits!(Dsp, I2s, LeftJustified, AnyFormat);
+
+/// Field writer. Its meaning depends on the format.
+///
+/// With DSP format, it select where the MSB is available after the LRC rising edge (mode A or
+/// B). With other formats, it swap the LRC phase.
+pub struct Lrp<T> {
+    cmd: DigitalAudioInterface<T>,
+}
+
+impl<T> Lrp<T> {
+    impl_bit!(DigitalAudioInterface<T>, 4);
+    impl_clear_bit!(DigitalAudioInterface<T>, 4);
+    impl_set_bit!(DigitalAudioInterface<T>, 4);
+}
+
+impl<MODE> Lrp<(Dsp, MODE)> {
+    ///MSB is available on the 1st BCLK rising edge after the LRC rising edge.
     #[must_use]
-    pub fn iwl_20_bits(self) -> DigitalAudioInterface {
-        self.bits(0b01)
+    pub const fn msb_on_first_bclk(self) -> DigitalAudioInterface<(Dsp, MODE)> {
+        self.clear_bit()
     }
+    ///MSB is available on the 2nd BCLK rising edge after the LRC rising edge.
     #[must_use]
-    pub fn iwl_16_bits(self) -> DigitalAudioInterface {
-        self.bits(0b00)
+    pub const fn msb_on_second_bclk(self) -> DigitalAudioInterface<(Dsp, MODE)> {
+        self.set_bit()
     }
 }
 
-pub struct Lrp {
-    cmd: DigitalAudioInterface,
+macro_rules! impl_lrc_phase {
+    ($($format:ty),*) => {
+        $(
+            impl<MODE> Lrp<($format, MODE)> {
+                ///Normal LRC phase.
+                #[must_use]
+                pub const fn normal(self) -> DigitalAudioInterface<($format, MODE)> {
+                    self.clear_bit()
+                }
+                ///Inverted LRC phase.
+                #[must_use]
+                pub const fn inverted(self) -> DigitalAudioInterface<($format, MODE)> {
+                    self.set_bit()
+                }
+            }
+        )*
+    };
 }
 
-impl Lrp {
-    impl_bit!(DigitalAudioInterface, 4);
-    impl_clear_bit!(DigitalAudioInterface, 4);
-    impl_set_bit!(DigitalAudioInterface, 4);
-}
+impl_lrc_phase!(I2s, LeftJustified, RightJustified);
 
-impl_toggle_writer!(Lrswap, DigitalAudioInterface, 5);
+impl_toggle_writer!(Lrswap<T>, DigitalAudioInterface<T>, 5);
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum MsV {
     Master = 0b1,
     Slave = 0b0,
 }
 
-pub struct Ms {
-    cmd: DigitalAudioInterface,
+/// Field writer. Select master or slave mode. Change the mode marker of the builder.
+pub struct Ms<T> {
+    cmd: DigitalAudioInterface<T>,
 }
 
-impl Ms {
-    impl_bit!(DigitalAudioInterface, 6);
-    impl_clear_bit!(DigitalAudioInterface, 6);
-    impl_set_bit!(DigitalAudioInterface, 6);
-    impl_clear_bit!(slave, DigitalAudioInterface, 6);
-    impl_set_bit!(master, DigitalAudioInterface, 6);
+impl<FORMAT, MODE> Ms<(FORMAT, MODE)> {
+    const fn write<M>(self, value: bool) -> DigitalAudioInterface<(FORMAT, M)> {
+        DigitalAudioInterface::<(FORMAT, M)> {
+            data: self.cmd.data & !(1 << 6) | (value as u16) << 6,
+            t: PhantomData::<(FORMAT, M)>,
+        }
+    }
 
+    ///Writes raw bit to the field. The mode is then only known at runtime.
     #[must_use]
-    pub fn variant(self, value: MsV) -> DigitalAudioInterface {
-        match value {
-            MsV::Slave => self.slave(),
-            MsV::Master => self.master(),
-        }
+    pub const fn bit(self, value: bool) -> DigitalAudioInterface<(FORMAT, AnyMode)> {
+        self.write(value)
+    }
+    /// Clears the bit field.
+    #[must_use]
+    pub const fn clear_bit(self) -> DigitalAudioInterface<(FORMAT, Slave)> {
+        self.write(false)
+    }
+    /// Sets the bit field.
+    #[must_use]
+    pub const fn set_bit(self) -> DigitalAudioInterface<(FORMAT, Master)> {
+        self.write(true)
+    }
+    #[must_use]
+    pub const fn slave(self) -> DigitalAudioInterface<(FORMAT, Slave)> {
+        self.write(false)
+    }
+    #[must_use]
+    pub const fn master(self) -> DigitalAudioInterface<(FORMAT, Master)> {
+        self.write(true)
+    }
+
+    ///Set the mode from a runtime value.
+    #[must_use]
+    pub const fn variant(self, value: MsV) -> DigitalAudioInterface<(FORMAT, AnyMode)> {
+        self.write(matches!(value, MsV::Master))
     }
 }
 
-impl_toggle_writer!(Bclkinv, DigitalAudioInterface, 7);
+impl_toggle_writer!(Bclkinv<T>, DigitalAudioInterface<T>, 7);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn to_command() {
+        let base = digital_audio_interface().format().left_justified();
+        let a = base.to_command();
+        let b = base.iwl().iwl_16_bits().to_command();
+        let test = [a.into_raw(), b.into_raw()];
+        let expected = [0b111 << 9 | 0b1001, 0b111 << 9 | 0b0001];
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+    }
+    #[test]
+    fn try_into_command() {
+        let cmd = digital_audio_interface()
+            .format()
+            .variant(FormatV::RigthJustified)
+            .iwl()
+            .variant(IwlV::Iwl32bits);
+        let test = cmd.try_into_command();
+        assert!(
+            test == Err(IncoherentFormat),
+            "Got {:?},expected {:?}",
+            test,
+            IncoherentFormat
+        );
+        let cmd = cmd.format().variant(FormatV::LeftJustified);
+        let test = cmd.try_into_command();
+        let expected = Ok(cmd.into_command());
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+    }
+    #[test]
+    fn right_justified_try_bits() {
+        let rj = digital_audio_interface().format().right_justified();
+        let test = rj.iwl().try_bits(0b11);
+        assert!(test.is_err(), "Got {:?},expected an error", test);
+        let test = rj.iwl().try_bits(0b100);
+        assert!(test.is_err(), "Got {:?},expected an error", test);
+        let test = rj.iwl().try_bits(0b01).map(|cmd| cmd.data);
+        let expected = Ok(0b111 << 9 | 0b0100);
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+    }
+    #[test]
+    fn right_justified_drops_32_bits() {
+        let cmd = digital_audio_interface()
+            .iwl()
+            .iwl_32_bits()
+            .format()
+            .right_justified();
+        let expected = 0b111 << 9 | 0b1000;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let test = cmd.try_into_command();
+        let expected = Ok(cmd.into_command());
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let cmd = digital_audio_interface()
+            .iwl()
+            .iwl_20_bits()
+            .format()
+            .right_justified();
+        let expected = 0b111 << 9 | 0b0100;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn format_dependent_lrp() {
+        let cmd = digital_audio_interface()
+            .format()
+            .dsp()
+            .lrp()
+            .msb_on_second_bclk();
+        let expected = 0b111 << 9 | 0b1_1011;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = digital_audio_interface()
+            .format()
+            .right_justified()
+            .lrp()
+            .inverted();
+        let expected = 0b111 << 9 | 0b1_1000;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = digital_audio_interface()
+            .format()
+            .variant(FormatV::LeftJustified)
+            .iwl()
+            .iwl_32_bits();
+        let expected = 0b111 << 9 | 0b1101;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn master_slave_mode() {
+        fn master_only(cmd: DigitalAudioInterface<(I2s, Master)>) -> u16 {
+            cmd.data
+        }
+        let test = master_only(digital_audio_interface().ms().master());
+        let expected = 0b111 << 9 | 0b100_1010;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+        let cmd = digital_audio_interface()
+            .ms()
+            .variant(MsV::Master)
+            .ms()
+            .slave();
+        let expected = 0b111 << 9 | 0b1010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn from_command() {
+        let cmd = digital_audio_interface()
+            .format()
+            .dsp()
+            .ms()
+            .master()
+            .into_command();
+        let test = DigitalAudioInterface::from_command(cmd).lrswap().set_bit();
+        let expected = 0b111 << 9 | 0b110_1011;
+        assert!(
+            test.data == expected,
+            "Got {:#b},expected {:#b}",
+            test.data,
+            expected
+        );
+    }
+    #[test]
+    fn for_i2s() {
+        let cmd = DigitalAudioInterface::for_i2s(IwlV::Iwl16bits);
+        let expected = 0b111 << 9 | 0b0010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn channel_order() {
+        let cmd = digital_audio_interface().channel_order(true);
+        let expected = 0b111 << 9 | 0b10_1010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = cmd.channel_order(false);
+        let expected = 0b111 << 9 | 0b1010;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+}
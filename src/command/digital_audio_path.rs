@@ -1,15 +1,24 @@
 //! Digital Audio Path configuration
-#![allow(clippy::new_without_default)]
 
+use crate::interface::Frame;
+use crate::registers;
 use crate::Command;
 use core::marker::PhantomData;
 
 /// Digital audio path configuration builder.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DigitalAudioPath {
     data: u16,
 }
 
+impl core::fmt::Debug for DigitalAudioPath {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
+
 impl Copy for DigitalAudioPath {}
 
 impl Clone for DigitalAudioPath {
@@ -18,30 +27,72 @@ impl Clone for DigitalAudioPath {
     }
 }
 
+impl From<DigitalAudioPath> for Frame {
+    fn from(builder: DigitalAudioPath) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for DigitalAudioPath {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Instanciate a builder for Digital audio path configuration.
-pub fn digital_audio_path() -> DigitalAudioPath {
+pub const fn digital_audio_path() -> DigitalAudioPath {
     DigitalAudioPath::new()
 }
 
 impl DigitalAudioPath {
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
-            data: 0b101 << 9 | 0b1000,
+            data: (registers::DIGITAL_AUDIO_PATH as u16) << 9
+                | registers::DIGITAL_AUDIO_PATH_DEFAULT,
         }
     }
-    pub fn adchpd(self) -> Adchpd {
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value();
+        new
+    }
+    ///Set ADC high pass filter and offset storage together.
+    ///
+    ///While enabled, the high pass filter continuously removes the DC offset. When it's disabled
+    ///with `store_offset` set, the last computed offset keeps being subtracted, otherwise the
+    ///offset is cleared. `store_offset` is meaningless while the filter is enabled, it's then
+    ///cleared.
+    #[must_use]
+    pub const fn high_pass(self, enabled: bool, store_offset: bool) -> Self {
+        self.adchpd()
+            .bit(!enabled)
+            .hpor()
+            .bit(!enabled && store_offset)
+    }
+    pub const fn adchpd(self) -> Adchpd {
         Adchpd { cmd: self }
     }
-    pub fn deemp(self) -> Deemp {
+    pub const fn deemp(self) -> Deemp {
         Deemp { cmd: self }
     }
-    pub fn dacmu(self) -> Dacmu {
+    pub const fn dacmu(self) -> Dacmu {
         Dacmu { cmd: self }
     }
-    pub fn hpor(self) -> Hpor {
+    pub const fn hpor(self) -> Hpor {
         Hpor { cmd: self }
     }
-    pub fn into_command(self) -> Command<()> {
+    pub const fn into_command(self) -> Command<()> {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()> {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
@@ -52,6 +103,8 @@ impl DigitalAudioPath {
 impl_toggle_writer!(Adchpd, DigitalAudioPath, 0);
 impl_toggle_writer!(Dacmu, DigitalAudioPath, 3);
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DeempV {
     Disable = 0b00,
     F32k = 0b01,
@@ -59,6 +112,20 @@ pub enum DeempV {
     F48k = 0b11,
 }
 
+impl DeempV {
+    ///Select the de-emphasis matching a sample rate in Hz.
+    ///
+    ///Rates without a standard de-emphasis curve give `Disable`.
+    pub const fn from_sample_rate(hz: u32) -> Self {
+        match hz {
+            32_000 => DeempV::F32k,
+            44_100 => DeempV::F44k1,
+            48_000 => DeempV::F48k,
+            _ => DeempV::Disable,
+        }
+    }
+}
+
 pub struct Deemp {
     cmd: DigitalAudioPath,
 }
@@ -67,7 +134,7 @@ impl Deemp {
     impl_bits!(DigitalAudioPath, 2, 1);
 
     #[must_use]
-    pub fn variant(self, value: DeempV) -> DigitalAudioPath {
+    pub const fn variant(self, value: DeempV) -> DigitalAudioPath {
         match value {
             DeempV::Disable => self.bits(0b00),
             DeempV::F32k => self.bits(0b01),
@@ -75,20 +142,27 @@ impl Deemp {
             DeempV::F48k => self.bits(0b11),
         }
     }
-    pub fn disable(self) -> DigitalAudioPath {
+    ///Set the de-emphasis matching a DAC sample rate in Hz, see [`DeempV::from_sample_rate`].
+    #[must_use]
+    pub const fn sample_rate(self, hz: u32) -> DigitalAudioPath {
+        self.variant(DeempV::from_sample_rate(hz))
+    }
+    pub const fn disable(self) -> DigitalAudioPath {
         self.bits(0b00)
     }
-    pub fn f32k(self) -> DigitalAudioPath {
+    pub const fn f32k(self) -> DigitalAudioPath {
         self.bits(0b01)
     }
-    pub fn f44k1(self) -> DigitalAudioPath {
+    pub const fn f44k1(self) -> DigitalAudioPath {
         self.bits(0b10)
     }
-    pub fn f48k(self) -> DigitalAudioPath {
+    pub const fn f48k(self) -> DigitalAudioPath {
         self.bits(0b11)
     }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum HporV {
     ClearOffset,
     StoreOffset,
@@ -106,10 +180,54 @@ impl Hpor {
     impl_set_bit!(store_offset, DigitalAudioPath, 4);
 
     #[must_use]
-    pub fn variant(self, value: HporV) -> DigitalAudioPath {
+    pub const fn variant(self, value: HporV) -> DigitalAudioPath {
         match value {
             HporV::ClearOffset => self.clear_offset(),
             HporV::StoreOffset => self.store_offset(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn deemp_sample_rate() {
+        let cases = [
+            (48_000, 0b11),
+            (44_100, 0b10),
+            (32_000, 0b01),
+            (96_000, 0b00),
+            (8_000, 0b00),
+        ];
+        for (hz, bits) in cases.iter() {
+            let cmd = digital_audio_path().deemp().sample_rate(*hz);
+            let expected = 0b101 << 9 | 0b1000 | bits << 1;
+            assert!(
+                cmd.data == expected,
+                "Got {:#b},expected {:#b}",
+                cmd.data,
+                expected
+            );
+        }
+    }
+    #[test]
+    fn high_pass() {
+        let cases = [
+            (true, false, 0b00),
+            (true, true, 0b00),
+            (false, false, 0b01),
+            (false, true, 0b1_0001),
+        ];
+        for (enabled, store_offset, bits) in cases.iter() {
+            let cmd = digital_audio_path().high_pass(*enabled, *store_offset);
+            let expected = 0b101 << 9 | 0b1000 | bits;
+            assert!(
+                cmd.data == expected,
+                "Got {:#b},expected {:#b}",
+                cmd.data,
+                expected
+            );
+        }
+    }
+}
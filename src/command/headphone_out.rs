@@ -1,7 +1,8 @@
 //! Headphone ouputs configuration
-#![allow(clippy::new_without_default)]
 
 use super::Command;
+use crate::interface::Frame;
+use crate::registers;
 use core::marker::PhantomData;
 
 #[path = "hp_vol_db.rs"]
@@ -14,13 +15,24 @@ pub struct Left;
 ///Marker indicating right channel
 pub struct Right;
 
+///Marker indicating both channels, written at once through the left register with the BOTH bit
+///set
+pub struct Both;
+
 ///Headphone out configuration builder
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Eq, PartialEq)]
 pub struct HeadphoneOut<CHANNEL> {
     data: u16,
     channel: PhantomData<CHANNEL>,
 }
 
+impl<CHANNEL> core::fmt::Debug for HeadphoneOut<CHANNEL> {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
+
 impl<CHANNEL> Copy for HeadphoneOut<CHANNEL> {}
 
 impl<CHANNEL> Clone for HeadphoneOut<CHANNEL> {
@@ -29,51 +41,168 @@ impl<CHANNEL> Clone for HeadphoneOut<CHANNEL> {
     }
 }
 
+impl<CHANNEL> From<HeadphoneOut<CHANNEL>> for Frame {
+    fn from(builder: HeadphoneOut<CHANNEL>) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for LeftHeadphoneOut {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for RightHeadphoneOut {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for BothHeadphoneOut {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 ///Marker indicating left headphone output concern
 pub type LeftHeadphoneOut = HeadphoneOut<Left>;
 
 ///Marker indicating left headphone output concern
 pub type RightHeadphoneOut = HeadphoneOut<Right>;
 
+///Marker indicating both headphone outputs concern
+pub type BothHeadphoneOut = HeadphoneOut<Both>;
+
 /// Instanciate a builder for left headphone output configuration.
-pub fn left_headphone_out() -> LeftHeadphoneOut {
+pub const fn left_headphone_out() -> LeftHeadphoneOut {
     LeftHeadphoneOut::new()
 }
 
 /// Instanciate a builder for right headphone output configuration.
-pub fn right_headphone_out() -> RightHeadphoneOut {
+pub const fn right_headphone_out() -> RightHeadphoneOut {
     RightHeadphoneOut::new()
 }
 
+/// Instanciate a builder configuring both headphone outputs with a single command.
+///
+/// The command is addressed to the left register with the BOTH bit set, the codec loads the same
+/// value into the right register. The BOTH bit can't be cleared from this builder.
+pub const fn both_headphone_out() -> BothHeadphoneOut {
+    BothHeadphoneOut::new()
+}
+
+///Set both headphone outputs volume with two commands, one per channel.
+///
+///Other fields are left to their reset values. Unlike [`both_headphone_volume`], channels are
+///written separately, so they can be briefly unbalanced between the two writes. This is
+///[`StereoPair::symmetric`](super::stereo::StereoPair::symmetric) without zero cross detection.
+pub const fn stereo_headphone_volume(volume: HpVoldB) -> [Command<()>; 2] {
+    super::stereo::StereoPair::symmetric(volume, false)
+}
+
+///Set both headphone outputs volume with a single command, using the `hpboth` bit.
+///
+///The command is addressed to the left register and the codec loads the same value into the
+///right one. Other fields are left to their reset values, for both channels.
+pub const fn both_headphone_volume(volume: HpVoldB) -> Command<()> {
+    both_headphone_out().hpvol().db(volume).into_command()
+}
+
 impl LeftHeadphoneOut {
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
-            data: 0x2 << 9 | 0b0_0111_1001,
+            data: (registers::LEFT_HEADPHONE_OUT as u16) << 9 | registers::HEADPHONE_OUT_DEFAULT,
             channel: PhantomData::<Left>,
         }
     }
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value();
+        new
+    }
+    ///Writer of the BOTH bit. When set, the codec also loads the written value into the
+    ///right register, so a single write configures both channels.
+    pub const fn hpboth(self) -> Hpboth<Left> {
+        Hpboth { cmd: self }
+    }
 }
 
 impl RightHeadphoneOut {
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
-            data: 0x3 << 9 | 0b0_0111_1001,
+            data: (registers::RIGHT_HEADPHONE_OUT as u16) << 9 | registers::HEADPHONE_OUT_DEFAULT,
             channel: PhantomData::<Right>,
         }
     }
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value();
+        new
+    }
+    ///Writer of the BOTH bit. When set, the codec also loads the written value into the
+    ///left register, so a single write configures both channels.
+    pub const fn hpboth(self) -> Hpboth<Right> {
+        Hpboth { cmd: self }
+    }
+}
+
+impl BothHeadphoneOut {
+    const fn new() -> Self {
+        Self {
+            data: (registers::LEFT_HEADPHONE_OUT as u16) << 9 | 0b1_0111_1001,
+            channel: PhantomData::<Both>,
+        }
+    }
+    ///Instantiate a builder loaded with the content of an existing command, for example the
+    ///shadow register of one channel to set both channels from it.
+    ///
+    ///Only the register content is used, the address is the one of this builder and the BOTH bit
+    ///is set.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value() | registers::BOTH;
+        new
+    }
 }
 
 impl<CHANNEL> HeadphoneOut<CHANNEL> {
-    pub fn hpvol(self) -> Hpvol<CHANNEL> {
+    pub const fn hpvol(self) -> Hpvol<CHANNEL> {
         Hpvol { cmd: self }
     }
-    pub fn zcen(self) -> Zcen<CHANNEL> {
+    ///Zero cross detection. When enabled, volume changes are delayed until the signal crosses
+    ///zero, to avoid clicks. See [`volume_with_zero_cross`](Self::volume_with_zero_cross).
+    pub const fn zcen(self) -> Zcen<CHANNEL> {
         Zcen { cmd: self }
     }
-    pub fn hpboth(self) -> Hpboth<CHANNEL> {
-        Hpboth { cmd: self }
+    ///Set the volume and enable zero cross detection in the same command.
+    ///
+    ///Since both fields are written at once, the new volume is already subject to zero cross
+    ///detection, there is no click from a volume write preceding the ZCEN write.
+    ///
+    ///The volume is only applied on a zero crossing of the audio signal, so without audio, for
+    ///example right after power up, the write may never take effect. Write the volume once
+    ///without zero cross detection if it must be applied unconditionally.
+    #[must_use]
+    pub const fn volume_with_zero_cross(self, volume: HpVoldB) -> Self {
+        self.hpvol().db(volume).zcen().enable()
     }
-    pub fn into_command(self) -> Command<()> {
+    pub const fn into_command(self) -> Command<()> {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()> {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
@@ -89,7 +218,7 @@ pub struct Hpvol<CHANNEL> {
 impl<CHANNEL> Hpvol<CHANNEL> {
     impl_bits!(HeadphoneOut<CHANNEL>, 7, 0);
     ///Set volume from a dB representation.
-    pub fn db(mut self, volume: HpVoldB) -> HeadphoneOut<CHANNEL> {
+    pub const fn db(mut self, volume: HpVoldB) -> HeadphoneOut<CHANNEL> {
         let mask = !((!0) << 7);
         self.cmd.data = self.cmd.data & !mask | (volume.into_raw() as u16);
         self.cmd
@@ -98,3 +227,66 @@ impl<CHANNEL> Hpvol<CHANNEL> {
 
 impl_toggle_writer!(Zcen<CHANNEL>, HeadphoneOut<CHANNEL>, 7);
 impl_toggle_writer!(Hpboth<CHANNEL>, HeadphoneOut<CHANNEL>, 8);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn both_from_command() {
+        let base = right_headphone_out().zcen().enable().into_command();
+        let test = BothHeadphoneOut::from_command(base)
+            .hpvol()
+            .db(HpVoldB::N6DB)
+            .into_command();
+        let expected = 0x2 << 9 | 0b1_1111_0011;
+        assert!(
+            test.data == expected,
+            "Got {:#b},expected {:#b}",
+            test.data,
+            expected
+        );
+    }
+    #[test]
+    fn stereo_headphone_volume_test() {
+        let [left, right] = stereo_headphone_volume(HpVoldB::N6DB);
+        let expected = [0x2 << 9 | 0b0_0111_0011, 0x3 << 9 | 0b0_0111_0011];
+        assert!(
+            [left.data, right.data] == expected,
+            "Got {:?},expected {:?}",
+            [left.data, right.data],
+            expected
+        );
+        let cmd = both_headphone_volume(HpVoldB::N6DB);
+        let expected = 0x2 << 9 | 0b1_0111_0011;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn volume_with_zero_cross() {
+        let cmd = right_headphone_out()
+            .volume_with_zero_cross(HpVoldB::N6DB)
+            .into_command();
+        let expected = 0x3 << 9 | 0b0_1111_0011;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn both_headphone_out_new() {
+        let cmd = both_headphone_out().into_command();
+        let expected = 0x2 << 9 | 0b1_0111_1001;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+}
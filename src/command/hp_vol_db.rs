@@ -24,7 +24,9 @@ impl HpVoldB {
     }
     ///Instanciate an `HpVoldB` from it's underlaying representation.
     ///
-    ///The raw value is clamped to fit in the range.
+    ///The raw value is clamped to fit in the range. Every code below `0b0110000` mutes the output,
+    ///so they all give [`MUTE`](Self::MUTE). This allows to display the volume of a shadow
+    ///register, with `Display`.
     pub const fn from_raw(raw: u8) -> Self {
         let raw = if raw < HpVoldB::MIN.inner {
             HpVoldB::MIN.inner
@@ -39,6 +41,10 @@ impl HpVoldB {
     pub const fn into_raw(self) -> u8 {
         self.inner
     }
+    ///Return `true` if this is the mute value.
+    pub const fn is_mute(self) -> bool {
+        self.inner == HpVoldB::MUTE.inner
+    }
     /// Scale a value into a HpVoldB. This function output an error when the input range is null or
     /// when the input is outside the range
     pub fn from_scaled(
@@ -56,9 +62,9 @@ impl HpVoldB {
 
         const MIN: u8 = HpVoldB::MIN.inner;
         const MAX: u8 = HpVoldB::MAX.inner;
-        let r1 = high_limit - low_limit;
-        let r2 = MAX - MIN;
-        let res = (((input - low_limit) * r2 as i16) + r1 / 2) / (r1) + MIN as i16;
+        let r1 = high_limit as i32 - low_limit as i32;
+        let r2 = (MAX - MIN) as i32;
+        let res = ((input as i32 - low_limit as i32) * r2 + r1 / 2) / r1 + MIN as i32;
         let res = unsafe { HpVoldB::from_raw_unchecked(res as _) };
         Ok(res)
     }
@@ -76,6 +82,39 @@ impl HpVoldB {
             self.inner -= 1;
         }
     }
+    ///Return the value one step (1dB) above, or `None` if it's already `HpVoldB::MAX`.
+    pub const fn step_up(self) -> Option<HpVoldB> {
+        if self.inner < HpVoldB::MAX.inner {
+            Some(Self {
+                inner: self.inner + 1,
+            })
+        } else {
+            None
+        }
+    }
+
+    ///Return the value one step (1dB) below, or `None` if it's already `HpVoldB::MIN`.
+    pub const fn step_down(self) -> Option<HpVoldB> {
+        if self.inner > HpVoldB::MIN.inner {
+            Some(Self {
+                inner: self.inner - 1,
+            })
+        } else {
+            None
+        }
+    }
+
+    ///Move the value by `n` steps. Saturated to `HpVoldB::MIN` and `HpVoldB::MAX`.
+    pub const fn saturating_step(self, n: i8) -> HpVoldB {
+        let raw = self.inner as i16 + n as i16;
+        if raw < HpVoldB::MIN.inner as i16 {
+            HpVoldB::MIN
+        } else if raw > HpVoldB::MAX.inner as i16 {
+            HpVoldB::MAX
+        } else {
+            Self { inner: raw as u8 }
+        }
+    }
     ///Represent a mute value
     pub const MUTE: HpVoldB = unsafe { HpVoldB::from_raw_unchecked(0b0101111) };
     ///Represent a value of -73dB
@@ -246,89 +285,91 @@ impl HpVoldB {
 impl fmt::Display for HpVoldB {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let fmt = match self.inner {
-            0b0110000 => "-73",
-            0b0110001 => "-72",
-            0b0110010 => "-71",
-            0b0110011 => "-70",
-            0b0110100 => "-69",
-            0b0110101 => "-68",
-            0b0110110 => "-67",
-            0b0110111 => "-66",
-            0b0111000 => "-65",
-            0b0111001 => "-64",
-            0b0111010 => "-63",
-            0b0111011 => "-62",
-            0b0111100 => "-61",
-            0b0111101 => "-60",
-            0b0111110 => "-59",
-            0b0111111 => "-58",
-            0b1000000 => "-57",
-            0b1000001 => "-56",
-            0b1000010 => "-55",
-            0b1000011 => "-54",
-            0b1000100 => "-53",
-            0b1000101 => "-52",
-            0b1000110 => "-51",
-            0b1000111 => "-50",
-            0b1001000 => "-49",
-            0b1001001 => "-48",
-            0b1001010 => "-47",
-            0b1001011 => "-46",
-            0b1001100 => "-45",
-            0b1001101 => "-44",
-            0b1001110 => "-43",
-            0b1001111 => "-42",
-            0b1010000 => "-41",
-            0b1010001 => "-40",
-            0b1010010 => "-39",
-            0b1010011 => "-38",
-            0b1010100 => "-37",
-            0b1010101 => "-36",
-            0b1010110 => "-35",
-            0b1010111 => "-34",
-            0b1011000 => "-33",
-            0b1011001 => "-32",
-            0b1011010 => "-31",
-            0b1011011 => "-30",
-            0b1011100 => "-29",
-            0b1011101 => "-28",
-            0b1011110 => "-27",
-            0b1011111 => "-26",
-            0b1100000 => "-25",
-            0b1100001 => "-24",
-            0b1100010 => "-23",
-            0b1100011 => "-22",
-            0b1100100 => "-21",
-            0b1100101 => "-20",
-            0b1100110 => "-19",
-            0b1100111 => "-18",
-            0b1101000 => "-17",
-            0b1101001 => "-16",
-            0b1101010 => "-15",
-            0b1101011 => "-14",
-            0b1101100 => "-13",
-            0b1101101 => "-12",
-            0b1101110 => "-11",
-            0b1101111 => "-10",
-            0b1110000 => "-9",
-            0b1110001 => "-8",
-            0b1110010 => "-7",
-            0b1110011 => "-6",
-            0b1110100 => "-5",
-            0b1110101 => "-4",
-            0b1110110 => "-3",
-            0b1110111 => "-2",
-            0b1111000 => "-1",
-            0b1111001 => "+0",
-            0b1111010 => "+1",
-            0b1111011 => "+2",
-            0b1111100 => "+3",
-            0b1111101 => "+4",
-            0b1111110 => "+5",
-            0b1111111 => "+6",
-            _ => unreachable!(),
+            0b0101111 => return f.write_str("mute"),
+            0b0110000 => "-73.0",
+            0b0110001 => "-72.0",
+            0b0110010 => "-71.0",
+            0b0110011 => "-70.0",
+            0b0110100 => "-69.0",
+            0b0110101 => "-68.0",
+            0b0110110 => "-67.0",
+            0b0110111 => "-66.0",
+            0b0111000 => "-65.0",
+            0b0111001 => "-64.0",
+            0b0111010 => "-63.0",
+            0b0111011 => "-62.0",
+            0b0111100 => "-61.0",
+            0b0111101 => "-60.0",
+            0b0111110 => "-59.0",
+            0b0111111 => "-58.0",
+            0b1000000 => "-57.0",
+            0b1000001 => "-56.0",
+            0b1000010 => "-55.0",
+            0b1000011 => "-54.0",
+            0b1000100 => "-53.0",
+            0b1000101 => "-52.0",
+            0b1000110 => "-51.0",
+            0b1000111 => "-50.0",
+            0b1001000 => "-49.0",
+            0b1001001 => "-48.0",
+            0b1001010 => "-47.0",
+            0b1001011 => "-46.0",
+            0b1001100 => "-45.0",
+            0b1001101 => "-44.0",
+            0b1001110 => "-43.0",
+            0b1001111 => "-42.0",
+            0b1010000 => "-41.0",
+            0b1010001 => "-40.0",
+            0b1010010 => "-39.0",
+            0b1010011 => "-38.0",
+            0b1010100 => "-37.0",
+            0b1010101 => "-36.0",
+            0b1010110 => "-35.0",
+            0b1010111 => "-34.0",
+            0b1011000 => "-33.0",
+            0b1011001 => "-32.0",
+            0b1011010 => "-31.0",
+            0b1011011 => "-30.0",
+            0b1011100 => "-29.0",
+            0b1011101 => "-28.0",
+            0b1011110 => "-27.0",
+            0b1011111 => "-26.0",
+            0b1100000 => "-25.0",
+            0b1100001 => "-24.0",
+            0b1100010 => "-23.0",
+            0b1100011 => "-22.0",
+            0b1100100 => "-21.0",
+            0b1100101 => "-20.0",
+            0b1100110 => "-19.0",
+            0b1100111 => "-18.0",
+            0b1101000 => "-17.0",
+            0b1101001 => "-16.0",
+            0b1101010 => "-15.0",
+            0b1101011 => "-14.0",
+            0b1101100 => "-13.0",
+            0b1101101 => "-12.0",
+            0b1101110 => "-11.0",
+            0b1101111 => "-10.0",
+            0b1110000 => "-9.0",
+            0b1110001 => "-8.0",
+            0b1110010 => "-7.0",
+            0b1110011 => "-6.0",
+            0b1110100 => "-5.0",
+            0b1110101 => "-4.0",
+            0b1110110 => "-3.0",
+            0b1110111 => "-2.0",
+            0b1111000 => "-1.0",
+            0b1111001 => "+0.0",
+            0b1111010 => "+1.0",
+            0b1111011 => "+2.0",
+            0b1111100 => "+3.0",
+            0b1111101 => "+4.0",
+            0b1111110 => "+5.0",
+            0b1111111 => "+6.0",
+            //only reachable through an invalid `from_raw_unchecked`
+            _ => return write!(f, "{:#b}", self.inner),
         };
-        write!(f, "{}dB", fmt)
+        write!(f, "{} dB", fmt)
     }
 }
 
@@ -336,6 +377,23 @@ impl fmt::Display for HpVoldB {
 mod tests {
     use super::*;
     #[test]
+    fn raw_round_trip() {
+        for raw in HpVoldB::MIN.inner..=HpVoldB::MAX.inner {
+            let db = unsafe { HpVoldB::from_raw_unchecked(raw) };
+            let test = HpVoldB::from_raw(db.into_raw());
+            assert!(test == db, "Got {:?}, expected {:?}", test, db);
+        }
+        for raw in 0..HpVoldB::MUTE.inner {
+            let test = HpVoldB::from_raw(raw);
+            assert!(
+                test.is_mute(),
+                "Got {:?}, expected {:?}",
+                test,
+                HpVoldB::MUTE
+            );
+        }
+    }
+    #[test]
     fn scale_test() {
         let db = HpVoldB::from_scaled(0, 255, 0).unwrap().inner;
         let expected = HpVoldB::MIN.inner;
@@ -357,6 +415,19 @@ mod tests {
         assert!(db == expected, "Got {:?},expected {:?}", db, expected);
     }
     #[test]
+    fn scale_full_range_test() {
+        let db = HpVoldB::from_scaled(i16::MIN, i16::MAX, i16::MIN)
+            .unwrap()
+            .inner;
+        let expected = HpVoldB::MIN.inner;
+        assert!(db == expected, "Got {:#b},expected {:#b}", db, expected);
+        let db = HpVoldB::from_scaled(i16::MAX, i16::MIN, i16::MIN)
+            .unwrap()
+            .inner;
+        let expected = HpVoldB::MAX.inner;
+        assert!(db == expected, "Got {:#b},expected {:#b}", db, expected);
+    }
+    #[test]
     fn increase_decrease_saturation_test() {
         let mut test = HpVoldB::MAX;
         test.increase();
@@ -375,4 +446,39 @@ mod tests {
             HpVoldB::MIN
         );
     }
+    #[test]
+    fn step_test() {
+        let test = HpVoldB::P0DB.step_up();
+        let expected = Some(HpVoldB::P1DB);
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = HpVoldB::P0DB.step_down();
+        let expected = Some(HpVoldB::N1DB);
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = HpVoldB::MAX.step_up();
+        assert!(test.is_none(), "Got {:?}, expected None", test);
+        let test = HpVoldB::MIN.step_down();
+        assert!(test.is_none(), "Got {:?}, expected None", test);
+        let test = HpVoldB::P0DB.saturating_step(-6);
+        let expected = HpVoldB::N6DB;
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+        let test = HpVoldB::P0DB.saturating_step(i8::MAX);
+        let expected = HpVoldB::MAX;
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+        let test = HpVoldB::N6DB.saturating_step(i8::MIN);
+        let expected = HpVoldB::MIN;
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn display_test() {
+        use std::format;
+        let test = format!("{}", HpVoldB::MUTE);
+        let expected = "mute";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+        let test = format!("{}", HpVoldB::N6DB);
+        let expected = "-6.0 dB";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+        let test = format!("{}", HpVoldB::P0DB);
+        let expected = "+0.0 dB";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+    }
 }
@@ -1,3 +1,4 @@
+use core::convert::TryFrom;
 use core::fmt;
 ///Error returned when trying to scale a value into InVoldB.
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -52,9 +53,9 @@ impl InVoldB {
 
         const MIN: u8 = InVoldB::MIN.inner;
         const MAX: u8 = InVoldB::MAX.inner;
-        let r1 = high_limit - low_limit;
-        let r2 = MAX - MIN;
-        let res = (((input - low_limit) * r2 as i16) + r1 / 2) / (r1) + MIN as i16;
+        let r1 = high_limit as i32 - low_limit as i32;
+        let r2 = (MAX - MIN) as i32;
+        let res = ((input as i32 - low_limit as i32) * r2 + r1 / 2) / r1 + MIN as i32;
         let res = unsafe { InVoldB::from_raw_unchecked(res as _) };
         Ok(res)
     }
@@ -141,45 +142,63 @@ impl InVoldB {
     pub const MIN: InVoldB = InVoldB::N34DB5;
     ///Represent the greatest value
     pub const MAX: InVoldB = InVoldB::P12DB;
+    ///Represent the reset value, +0dB
+    pub const ZERO: InVoldB = InVoldB::P0DB;
+}
+
+impl TryFrom<f32> for InVoldB {
+    type Error = InVoldBScaleError;
+    ///Convert a value in dB into the nearest `InVoldB`.
+    ///
+    ///Values exactly between two steps are rounded up, so `-0.75` gives `+0dB`. Values outside
+    ///`-34.5..=12.0` (and NaN) output an `OutOfRange` error.
+    fn try_from(value: f32) -> Result<Self, Self::Error> {
+        if !(-34.5..=12.0).contains(&value) {
+            return Err(InVoldBScaleError::OutOfRange);
+        }
+        let step = ((value + 34.5) / 1.5 + 0.5) as u8;
+        Ok(InVoldB::from_raw(step))
+    }
 }
 impl fmt::Display for InVoldB {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let fmt = match self.inner {
             0b00000 => "-34.5",
-            0b00001 => "-33",
+            0b00001 => "-33.0",
             0b00010 => "-31.5",
-            0b00011 => "-30",
+            0b00011 => "-30.0",
             0b00100 => "-28.5",
-            0b00101 => "-27",
+            0b00101 => "-27.0",
             0b00110 => "-25.5",
-            0b00111 => "-24",
+            0b00111 => "-24.0",
             0b01000 => "-22.5",
-            0b01001 => "-21",
+            0b01001 => "-21.0",
             0b01010 => "-19.5",
-            0b01011 => "-18",
+            0b01011 => "-18.0",
             0b01100 => "-16.5",
-            0b01101 => "-15",
+            0b01101 => "-15.0",
             0b01110 => "-13.5",
-            0b01111 => "-12",
+            0b01111 => "-12.0",
             0b10000 => "-10.5",
-            0b10001 => "-9",
+            0b10001 => "-9.0",
             0b10010 => "-7.5",
-            0b10011 => "-6",
+            0b10011 => "-6.0",
             0b10100 => "-4.5",
-            0b10101 => "-3",
+            0b10101 => "-3.0",
             0b10110 => "-1.5",
-            0b10111 => "+0",
+            0b10111 => "+0.0",
             0b11000 => "+1.5",
-            0b11001 => "+3",
+            0b11001 => "+3.0",
             0b11010 => "+4.5",
-            0b11011 => "+6",
+            0b11011 => "+6.0",
             0b11100 => "+7.5",
-            0b11101 => "+9",
+            0b11101 => "+9.0",
             0b11110 => "+10.5",
-            0b11111 => "+12",
-            _ => unreachable!(),
+            0b11111 => "+12.0",
+            //only reachable through an invalid `from_raw_unchecked`
+            _ => return write!(f, "{:#b}", self.inner),
         };
-        write!(f, "{}dB", fmt)
+        write!(f, "{} dB", fmt)
     }
 }
 
@@ -187,6 +206,14 @@ impl fmt::Display for InVoldB {
 mod tests {
     use super::*;
     #[test]
+    fn raw_round_trip() {
+        for raw in InVoldB::MIN.inner..=InVoldB::MAX.inner {
+            let db = unsafe { InVoldB::from_raw_unchecked(raw) };
+            let test = InVoldB::from_raw(db.into_raw());
+            assert!(test == db, "Got {:?}, expected {:?}", test, db);
+        }
+    }
+    #[test]
     fn scale_test() {
         let db = InVoldB::from_scaled(0, 255, 0).unwrap().inner;
         let expected = InVoldB::MIN.inner;
@@ -208,6 +235,19 @@ mod tests {
         assert!(db == expected, "Got {:?},expected {:?}", db, expected);
     }
     #[test]
+    fn scale_full_range_test() {
+        let db = InVoldB::from_scaled(i16::MIN, i16::MAX, i16::MIN)
+            .unwrap()
+            .inner;
+        let expected = InVoldB::MIN.inner;
+        assert!(db == expected, "Got {:#b},expected {:#b}", db, expected);
+        let db = InVoldB::from_scaled(i16::MAX, i16::MIN, i16::MIN)
+            .unwrap()
+            .inner;
+        let expected = InVoldB::MAX.inner;
+        assert!(db == expected, "Got {:#b},expected {:#b}", db, expected);
+    }
+    #[test]
     fn increase_decrease_saturation_test() {
         let mut test = InVoldB::MAX;
         test.increase();
@@ -226,4 +266,38 @@ mod tests {
             InVoldB::MIN
         );
     }
+    #[test]
+    fn try_from_f32_test() {
+        let cases = [
+            (-34.5, Ok(InVoldB::MIN)),
+            (12.0, Ok(InVoldB::MAX)),
+            (0.0, Ok(InVoldB::ZERO)),
+            (-1.0, Ok(InVoldB::N1DB5)),
+            (-0.75, Ok(InVoldB::P0DB)),
+            (11.9, Ok(InVoldB::MAX)),
+            (-34.6, Err(InVoldBScaleError::OutOfRange)),
+            (12.1, Err(InVoldBScaleError::OutOfRange)),
+            (f32::NAN, Err(InVoldBScaleError::OutOfRange)),
+        ];
+        for (value, expected) in cases.iter() {
+            let test = InVoldB::try_from(*value);
+            assert!(
+                test == *expected,
+                "Got {:?}, expected {:?} for {}",
+                test,
+                expected,
+                value
+            );
+        }
+    }
+    #[test]
+    fn display_test() {
+        use std::format;
+        let test = format!("{}", InVoldB::N34DB5);
+        let expected = "-34.5 dB";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+        let test = format!("{}", InVoldB::P12DB);
+        let expected = "+12.0 dB";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+    }
 }
@@ -1,7 +1,8 @@
 //! Line inputs configuration
-#![allow(clippy::new_without_default)]
 
 use super::Command;
+use crate::interface::Frame;
+use crate::registers;
 use core::marker::PhantomData;
 
 #[path = "in_vol_db.rs"]
@@ -14,13 +15,24 @@ pub struct Left;
 ///Marker indicating right channel
 pub struct Right;
 
+///Marker indicating both channels, written at once through the left register with the BOTH bit
+///set
+pub struct Both;
+
 /// Line in configuration builder.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Eq, PartialEq)]
 pub struct LineIn<CHANNEL> {
     data: u16,
     channel: PhantomData<CHANNEL>,
 }
 
+impl<CHANNEL> core::fmt::Debug for LineIn<CHANNEL> {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
+
 impl<CHANNEL> Copy for LineIn<CHANNEL> {}
 
 impl<CHANNEL> Clone for LineIn<CHANNEL> {
@@ -29,51 +41,157 @@ impl<CHANNEL> Clone for LineIn<CHANNEL> {
     }
 }
 
+impl<CHANNEL> From<LineIn<CHANNEL>> for Frame {
+    fn from(builder: LineIn<CHANNEL>) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for LeftLineIn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for RightLineIn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for BothLineIn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Left line in configuration builder.
 pub type LeftLineIn = LineIn<Left>;
 
 /// Right line in configuration builder.
 pub type RightLineIn = LineIn<Right>;
 
+/// Configuration builder for both line in channels.
+pub type BothLineIn = LineIn<Both>;
+
 /// Instanciate a builder for left line in configuration.
-pub fn left_line_in() -> LeftLineIn {
+pub const fn left_line_in() -> LeftLineIn {
     LeftLineIn::new()
 }
 
 /// Instanciate a builder for right line in configuration.
-pub fn right_line_in() -> RightLineIn {
+pub const fn right_line_in() -> RightLineIn {
     RightLineIn::new()
 }
 
+/// Instanciate a builder configuring both line in channels with a single command.
+///
+/// The command is addressed to the left register with the BOTH bit set, the codec loads the same
+/// value into the right register. The BOTH bit can't be cleared from this builder.
+pub const fn both_line_in() -> BothLineIn {
+    BothLineIn::new()
+}
+
+///Set both line inputs volume with two commands, one per channel.
+///
+///Other fields are left to their reset values, so inputs stay muted. Unlike
+///[`both_line_in_volume`], channels are written separately, so they can be briefly unbalanced
+///between the two writes.
+pub const fn stereo_line_in_volume(volume: InVoldB) -> [Command<()>; 2] {
+    [
+        left_line_in().invol().db(volume).into_command(),
+        right_line_in().invol().db(volume).into_command(),
+    ]
+}
+
+///Set both line inputs volume with a single command, using the `inboth` bit.
+///
+///The command is addressed to the left register and the codec loads the same value into the
+///right one. Other fields are left to their reset values for both channels, so inputs stay muted.
+pub const fn both_line_in_volume(volume: InVoldB) -> Command<()> {
+    both_line_in().invol().db(volume).into_command()
+}
+
 impl LeftLineIn {
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
-            data: 0b0_1001_0111,
+            data: (registers::LEFT_LINE_IN as u16) << 9 | registers::LINE_IN_DEFAULT,
             channel: PhantomData::<Left>,
         }
     }
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value();
+        new
+    }
+    ///Writer of the BOTH bit. When set, the codec also loads the written value into the
+    ///right register, so a single write configures both channels.
+    pub const fn inboth(self) -> Inboth<Left> {
+        Inboth { cmd: self }
+    }
 }
 
 impl RightLineIn {
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
-            data: 0x1 << 9 | 0b0_1001_0111,
+            data: (registers::RIGHT_LINE_IN as u16) << 9 | registers::LINE_IN_DEFAULT,
             channel: PhantomData::<Right>,
         }
     }
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value();
+        new
+    }
+    ///Writer of the BOTH bit. When set, the codec also loads the written value into the
+    ///left register, so a single write configures both channels.
+    pub const fn inboth(self) -> Inboth<Right> {
+        Inboth { cmd: self }
+    }
+}
+
+impl BothLineIn {
+    const fn new() -> Self {
+        Self {
+            data: (registers::LEFT_LINE_IN as u16) << 9 | 0b1_1001_0111,
+            channel: PhantomData::<Both>,
+        }
+    }
+    ///Instantiate a builder loaded with the content of an existing command, for example the
+    ///shadow register of one channel to set both channels from it.
+    ///
+    ///Only the register content is used, the address is the one of this builder and the BOTH bit
+    ///is set.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value() | registers::BOTH;
+        new
+    }
 }
 
 impl<CHANNEL> LineIn<CHANNEL> {
-    pub fn invol(self) -> Invol<CHANNEL> {
+    pub const fn invol(self) -> Invol<CHANNEL> {
         Invol { cmd: self }
     }
-    pub fn inmute(self) -> Inmute<CHANNEL> {
+    pub const fn inmute(self) -> Inmute<CHANNEL> {
         Inmute { cmd: self }
     }
-    pub fn inboth(self) -> Inboth<CHANNEL> {
-        Inboth { cmd: self }
+    pub const fn into_command(self) -> Command<()> {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
     }
-    pub fn into_command(self) -> Command<()> {
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()> {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
@@ -89,7 +207,7 @@ pub struct Invol<CHANNEL> {
 impl<CHANNEL> Invol<CHANNEL> {
     impl_bits!(LineIn<CHANNEL>, 5, 0);
     ///Set volume from a dB representation.
-    pub fn db(mut self, volume: InVoldB) -> LineIn<CHANNEL> {
+    pub const fn db(mut self, volume: InVoldB) -> LineIn<CHANNEL> {
         let mask = !((!0) << 5);
         self.cmd.data = self.cmd.data & !mask | (volume.into_raw() as u16);
         self.cmd
@@ -103,6 +221,13 @@ impl_toggle_writer!(Inboth<CHANNEL>, LineIn<CHANNEL>, 8);
 mod tests {
     use super::*;
     #[test]
+    fn both_from_command() {
+        let base = right_line_in().inmute().disable().into_command();
+        let test = BothLineIn::from_command(base).into_command().into_raw();
+        let expected = 0b1_0001_0111;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+    }
+    #[test]
     fn left_line_in_new() {
         let cmd = left_line_in().into_command();
         let expected = 0b0000_0000_1001_0111;
@@ -138,4 +263,51 @@ mod tests {
             expected
         )
     }
+    #[test]
+    fn invol_db() {
+        let cases = [
+            (InVoldB::P0DB, 0b0_1001_0111),
+            (InVoldB::MIN, 0b0_1000_0000),
+            (InVoldB::MAX, 0b0_1001_1111),
+        ];
+        for (db, expected) in cases.iter() {
+            let cmd = left_line_in().invol().db(*db);
+            assert!(
+                cmd.data == *expected,
+                "Got {:#b},expected {:#b}",
+                cmd.data,
+                expected
+            )
+        }
+    }
+    #[test]
+    fn stereo_line_in_volume_test() {
+        let [left, right] = stereo_line_in_volume(InVoldB::N6DB);
+        let expected = [0b0_1001_0011, 0x1 << 9 | 0b0_1001_0011];
+        assert!(
+            [left.data, right.data] == expected,
+            "Got {:?},expected {:?}",
+            [left.data, right.data],
+            expected
+        );
+        let cmd = both_line_in_volume(InVoldB::N6DB);
+        let expected = 0b1_1001_0011;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn both_line_in_new() {
+        let cmd = both_line_in().into_command();
+        let expected = 0b1_1001_0111;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
 }
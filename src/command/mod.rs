@@ -9,7 +9,12 @@ pub mod digital_audio_path;
 pub mod headphone_out;
 pub mod line_in;
 pub mod power_down;
+#[cfg(test)]
+mod property_tests;
 pub mod sampling;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod stereo;
 
 #[doc(inline)]
 pub use active_control::active_control;
@@ -20,11 +25,16 @@ pub use digital_audio_interface::digital_audio_interface;
 #[doc(inline)]
 pub use digital_audio_path::digital_audio_path;
 #[doc(inline)]
-pub use headphone_out::{left_headphone_out, right_headphone_out};
+pub use headphone_out::{
+    both_headphone_out, both_headphone_volume, left_headphone_out, right_headphone_out,
+    stereo_headphone_volume,
+};
 #[doc(inline)]
-pub use line_in::{left_line_in, right_line_in};
+pub use line_in::{
+    both_line_in, both_line_in_volume, left_line_in, right_line_in, stereo_line_in_volume,
+};
 #[doc(inline)]
-pub use power_down::power_down;
+pub use power_down::{power_down, power_down_from};
 #[doc(inline)]
 pub use reset::reset;
 #[doc(inline)]
@@ -34,11 +44,13 @@ pub use sampling::sampling_with_mclk;
 
 pub mod reset {
     //! Reset the device
-    #![allow(clippy::new_without_default)]
+    use crate::interface::Frame;
+    use crate::registers;
     use crate::Command;
     use core::marker::PhantomData;
     /// Reset command builder.
     #[derive(Debug, Eq, PartialEq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
     pub struct Reset {
         data: u16,
     }
@@ -51,18 +63,41 @@ pub mod reset {
         }
     }
 
+    impl From<Reset> for Frame {
+        fn from(builder: Reset) -> Frame {
+            builder.into_command().into()
+        }
+    }
+
+    impl Default for Reset {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     /// Instantiate a reset command builder.
-    pub fn reset() -> Reset {
+    ///
+    /// Every register, including power down, is restored to its reset value. To only power down
+    /// the codec and keep its configuration, see
+    /// [`PowerDown::safe_shutdown`](crate::command::power_down::PowerDown::safe_shutdown).
+    pub const fn reset() -> Reset {
         Reset::new()
     }
 
     impl Reset {
-        fn new() -> Self {
+        const fn new() -> Self {
             Self {
-                data: 0b110 << 9 | 0b1001_1111,
+                data: (registers::RESET as u16) << 9,
+            }
+        }
+        pub const fn into_command(self) -> Command<()> {
+            Command::<()> {
+                data: self.data,
+                t: PhantomData::<()>,
             }
         }
-        pub fn into_command(self) -> Command<()> {
+        ///Instantiate a command, keeping the builder to derive other commands from it.
+        pub const fn to_command(&self) -> Command<()> {
             Command::<()> {
                 data: self.data,
                 t: PhantomData::<()>,
@@ -71,13 +106,113 @@ pub mod reset {
     }
 }
 
+///Error returned when a raw value doesn't fit in a field.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct OutOfRange;
+
+///Error returned when a control word doesn't address a register.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidRegister;
+
 ///Represent a command to send to the codec, that is register address and content to write in it.
-#[derive(Debug, Eq, PartialEq)]
+///
+///Commands are ordered by their raw control word, that is by register address then content.
 pub struct Command<T> {
     pub(crate) data: u16,
     t: PhantomData<T>,
 }
 
+impl Command<()> {
+    ///Instantiate a command from a raw 16 bits control word.
+    ///
+    ///# Safety
+    ///
+    ///The control word is sent as is, it may target a reserved register or contain an invalid bit
+    ///combination. Please read the datasheet.
+    pub const unsafe fn from_raw(data: u16) -> Self {
+        Command::<()> {
+            data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Advanced escape hatch writing bits the builders don't model: clear `mask` then set
+    ///`value & mask`.
+    ///
+    ///Only the 9 bits register content can be modified, upper bits of `mask` are ignored so the
+    ///address is kept.
+    ///
+    ///# Safety
+    ///
+    ///Like [`from_raw`](Self::from_raw), the result isn't checked, it may contain an invalid bit
+    ///combination. Please read the datasheet.
+    #[must_use]
+    pub const unsafe fn with_bits(mut self, mask: u16, value: u16) -> Self {
+        let mask = mask & 0x1FF;
+        self.data = self.data & !mask | value & mask;
+        self
+    }
+}
+
+impl core::convert::TryFrom<u16> for Command<()> {
+    type Error = InvalidRegister;
+    ///Convert a raw 16 bits control word, fails if the address is a reserved one.
+    ///
+    ///The register content isn't checked.
+    fn try_from(data: u16) -> Result<Self, Self::Error> {
+        use crate::registers::*;
+        match (data >> 9) as u8 {
+            LEFT_LINE_IN..=ACTIVE_CONTROL | RESET => Ok(unsafe { Command::from_raw(data) }),
+            _ => Err(InvalidRegister),
+        }
+    }
+}
+
+impl<T> Command<T> {
+    ///Return the raw 16 bits control word, that is address and value.
+    pub const fn into_raw(self) -> u16 {
+        self.data
+    }
+    ///Return the address of the targeted register.
+    pub const fn address(&self) -> u8 {
+        (self.data >> 9) as u8
+    }
+    ///Return the 9 bits value written into the register.
+    pub const fn value(&self) -> u16 {
+        self.data & 0x1FF
+    }
+    ///Return the address of the targeted register alongside the command, for routing layers
+    ///needing to translate commands per register.
+    pub const fn into_addressed(self) -> (u8, Command<()>) {
+        (
+            self.address(),
+            Command::<()> {
+                data: self.data,
+                t: PhantomData::<()>,
+            },
+        )
+    }
+    ///Return `true` if both commands target the same register.
+    pub const fn same_register<T2>(&self, other: &Command<T2>) -> bool {
+        self.address() == other.address()
+    }
+}
+
+impl<T> core::fmt::Debug for Command<T> {
+    ///Print the decoded register rather than the raw control word.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T> defmt::Format for Command<T> {
+    ///Print the decoded register rather than the raw control word.
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}", crate::decode::decode(self.data))
+    }
+}
+
 impl<T> Copy for Command<T> {}
 
 impl<T> Clone for Command<T> {
@@ -86,10 +221,172 @@ impl<T> Clone for Command<T> {
     }
 }
 
+impl<T> PartialEq for Command<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<T> Eq for Command<T> {}
+
+impl<T> PartialOrd for Command<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Command<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.data.cmp(&other.data)
+    }
+}
+
+impl<T> core::hash::Hash for Command<T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     fn _should_compile() {
         left_headphone_out().hpvol().bits(0b111111).into_command();
     }
+    #[test]
+    fn default_is_reset_state() {
+        fn default_cmd<T: Default>(into_command: fn(T) -> Command<()>) -> Command<()> {
+            into_command(T::default())
+        }
+        let test = default_cmd(power_down::PowerDown::into_command);
+        let expected = power_down().into_command();
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = default_cmd(line_in::RightLineIn::into_command);
+        let expected = right_line_in().into_command();
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = default_cmd(digital_audio_interface::DigitalAudioInterface::into_command);
+        let expected = digital_audio_interface().into_command();
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn const_reset() {
+        const RESET_FRAME: u16 = reset().into_command().into_raw();
+        let expected = 0b1111 << 9;
+        assert!(
+            RESET_FRAME == expected,
+            "Got {:#b}, expected {:#b}",
+            RESET_FRAME,
+            expected
+        );
+    }
+    #[test]
+    fn const_preset() {
+        const PRESET: [u16; 3] = [
+            power_down().poweroff().disable().into_command().into_raw(),
+            digital_audio_interface()
+                .format()
+                .dsp()
+                .iwl()
+                .iwl_24_bits()
+                .into_command()
+                .into_raw(),
+            sampling().sr().sr_0b0110().into_command().into_raw(),
+        ];
+        let expected = [
+            0b110 << 9 | 0b0001_1111,
+            0b111 << 9 | 0b1011,
+            0b1000 << 9 | 0b1_1000,
+        ];
+        assert!(
+            PRESET == expected,
+            "Got {:?}, expected {:?}",
+            PRESET,
+            expected
+        );
+    }
+    #[test]
+    fn raw_round_trip() {
+        let cmd = sampling().sr().sr_0b0110().into_command();
+        let raw = cmd.into_raw();
+        let test = unsafe { Command::from_raw(raw) };
+        assert!(test == cmd, "Got {:?}, expected {:?}", test, cmd);
+    }
+    #[test]
+    fn bytes_from_command() {
+        let bytes: [u8; 2] = active_control().active().into_command().into();
+        let expected = [0b0001_0010, 0b0000_0001];
+        assert!(
+            bytes == expected,
+            "Got {:?}, expected {:?}",
+            bytes,
+            expected
+        );
+    }
+    #[test]
+    fn try_from_u16() {
+        use core::convert::TryFrom;
+        let test = Command::try_from(0b1001 << 9 | 0b1);
+        let expected = Ok(active_control().active().into_command());
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let test = Command::try_from(0b1111 << 9);
+        let expected = Ok(reset().into_command());
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let test = Command::try_from(0b1010 << 9);
+        assert!(
+            test == Err(InvalidRegister),
+            "Got {:?},expected {:?}",
+            test,
+            InvalidRegister
+        );
+    }
+    #[test]
+    fn ordered_by_register() {
+        use std::collections::BTreeSet;
+        let set: BTreeSet<_> = [
+            active_control().into_command(),
+            power_down().into_command(),
+            active_control().into_command(),
+            left_line_in().into_command(),
+        ]
+        .iter()
+        .copied()
+        .collect();
+        let test: std::vec::Vec<u8> = set.iter().map(|cmd| cmd.address()).collect();
+        let expected = [0, 6, 9];
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+    }
+    #[test]
+    fn with_bits() {
+        let cmd = power_down().into_command();
+        let test = unsafe { cmd.with_bits(0xF0F0, 0xFFFF) }.into_raw();
+        let expected = 0b110 << 9 | 0b1111_1111;
+        assert!(test == expected, "Got {:#b},expected {:#b}", test, expected);
+    }
+    #[test]
+    fn into_addressed() {
+        let cmd = power_down().poweroff().disable().into_command();
+        let test = cmd.into_addressed();
+        let expected = (6, cmd);
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+    }
+    #[test]
+    fn same_register() {
+        let a = power_down().poweroff().disable().into_command();
+        let b = power_down().into_command();
+        let c = active_control().into_command();
+        assert!(a.same_register(&b));
+        assert!(!a.same_register(&c));
+    }
+    #[test]
+    fn address_and_value() {
+        let cmd = power_down().poweroff().disable().into_command();
+        let (addr, value) = (cmd.address(), cmd.value());
+        let expected = (6, 0b0001_1111);
+        assert!(
+            (addr, value) == expected,
+            "Got {:?}, expected {:?}",
+            (addr, value),
+            expected
+        );
+    }
 }
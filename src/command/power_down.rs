@@ -1,59 +1,220 @@
 //! Power down configuration
-#![allow(clippy::new_without_default)]
+//!
+//! Powering down the oscillator also stops CLKOUT, so a chip clocked from CLKOUT would lose its
+//! clock. To make this explicit, the builder track in its type when CLKOUT is powered down with
+//! the `clkoutpd` writer, and only then offer `oscpd_safe` and a safe `oscpd` writer. Before
+//! that, the `oscpd` writer can only power the oscillator up, or write a raw bit in an `unsafe`
+//! call. Both bits belong to the same register, so CLKOUT is powered down in the same write, or
+//! before when the builder was sent in between.
+//!
+//! ## Example
+//! ```
+//! # use wm8731_alt::command::power_down::*;
+//! let cmd = power_down().clkoutpd().enable().oscpd_safe().into_command();
+//! let cmd = power_down().clkoutpd().enable().oscpd().enable().into_command();
+//! ```
+//!
+//! ## Example of bad usage
+//! Following examples show incorrect usage and don't compile, CLKOUT isn't known to be powered
+//! down.
+//! ```compile_fail
+//! # use wm8731_alt::command::power_down::*;
+//! let cmd = power_down().oscpd_safe();
+//! ```
+//! ```compile_fail
+//! # use wm8731_alt::command::power_down::*;
+//! let cmd = power_down().clkoutpd().disable().oscpd().enable();
+//! ```
 
+use crate::command::digital_audio_path::DigitalAudioPath;
+use crate::interface::Frame;
+use crate::registers;
 use crate::Command;
 use core::marker::PhantomData;
 
+/// Marker used to indicate CLKOUT is powered down.
+pub struct ClkoutPoweredDown;
+/// Marker used to indicate CLKOUT state isn't tracked, it may be powered.
+pub struct AnyClkout;
+
 /// Power down configuration builder.
-#[derive(Debug, Eq, PartialEq)]
-pub struct PowerDown {
+#[derive(Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerDown<CLKOUT = AnyClkout> {
     data: u16,
+    clkout: PhantomData<CLKOUT>,
 }
 
-impl Copy for PowerDown {}
+impl<CLKOUT> core::fmt::Debug for PowerDown<CLKOUT> {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
 
-impl Clone for PowerDown {
+impl<CLKOUT> Copy for PowerDown<CLKOUT> {}
+
+impl<CLKOUT> Clone for PowerDown<CLKOUT> {
     fn clone(&self) -> Self {
         *self
     }
 }
 
+impl<CLKOUT> From<PowerDown<CLKOUT>> for Frame {
+    fn from(builder: PowerDown<CLKOUT>) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for PowerDown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Instanciate a builder for power down configuration.
-pub fn power_down() -> PowerDown {
+pub const fn power_down() -> PowerDown {
     PowerDown::new()
 }
 
+///Instanciate a builder for power down configuration, starting from a register value instead of
+///the reset value.
+///
+///`base` is the 9 bits register content, typically a shadow register from
+///[`Wm8731::register`](crate::Wm8731::register). This allows to change one block without
+///reasserting the reset value of the others.
+pub const fn power_down_from(base: u16) -> PowerDown {
+    PowerDown::new().with_data((registers::POWER_DOWN as u16) << 9 | base & 0x1FF)
+}
+
 impl PowerDown {
-    fn new() -> Self {
+    const fn new() -> Self {
+        Self {
+            data: (registers::POWER_DOWN as u16) << 9 | registers::POWER_DOWN_DEFAULT,
+            clkout: PhantomData::<AnyClkout>,
+        }
+    }
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    pub const fn from_command(cmd: Command<()>) -> Self {
+        let mut new = Self::new();
+        new.data = new.data & !0x1FF | cmd.value();
+        new
+    }
+    ///Preset with every block powered up except outputs.
+    ///
+    ///This is the first step of the anti-pop power up sequence recommended by the datasheet,
+    ///outputs are powered up once the DAC is unmuted. See
+    ///[`sequence::power_up`](crate::sequence::power_up).
+    pub const fn power_up_analog() -> Self {
         Self {
-            data: 0b110 << 9 | 0b1001_1111,
+            data: (registers::POWER_DOWN as u16) << 9 | 0b0001_0000,
+            clkout: PhantomData::<AnyClkout>,
         }
     }
-    pub fn lineinpd(self) -> Lineinpd {
+    ///Pop free shutdown sequence, for a running codec.
+    ///
+    ///Unlike [`reset`](crate::command::reset()), which restores every register to its reset
+    ///value, this only acts on power, other registers keep their configuration. `path` and `power`
+    ///are the current content of the digital audio path and power down registers, so fields not
+    ///involved in the shutdown are written unchanged, see
+    ///[`Wm8731::safe_shutdown`](crate::Wm8731::safe_shutdown). Frames are, in this order:
+    /// 1. Digital audio path: DAC muted.
+    /// 2. Power down: outputs powered down.
+    /// 3. Power down: every analogue block powered down.
+    /// 4. Power down: everything powered down, `poweroff` included, see [`all`](Self::all).
+    pub fn safe_shutdown<CLKOUT>(path: DigitalAudioPath, power: PowerDown<CLKOUT>) -> [Frame; 4] {
+        let power = power.outpd().enable();
+        let analog = power.with_data::<CLKOUT>(power.data | 0b0001_1111);
+        [
+            path.dacmu().enable().into(),
+            power.into(),
+            analog.into(),
+            analog.all().into(),
+        ]
+    }
+    ///Preset with every analogue block powered down, oscillator and clock output are left powered.
+    ///
+    ///Outputs are powered down along with the other blocks, to avoid pop, it should be preceded
+    ///by a command powering down outputs only.
+    pub const fn power_down_analog() -> Self {
+        Self {
+            data: (registers::POWER_DOWN as u16) << 9 | 0b0001_1111,
+            clkout: PhantomData::<AnyClkout>,
+        }
+    }
+}
+
+impl<CLKOUT> PowerDown<CLKOUT> {
+    const fn with_data<C>(self, data: u16) -> PowerDown<C> {
+        PowerDown::<C> {
+            data,
+            clkout: PhantomData::<C>,
+        }
+    }
+    ///Set every power down bit, including `poweroff`.
+    #[must_use]
+    pub const fn all(self) -> PowerDown<ClkoutPoweredDown> {
+        let data = self.data | 0xFF;
+        self.with_data(data)
+    }
+    ///Clear every power down bit, the codec is fully powered.
+    #[must_use]
+    pub const fn none(self) -> PowerDown {
+        let data = self.data & !0xFF;
+        self.with_data(data)
+    }
+    ///Power up CLKOUT, with the oscillator it relies on.
+    ///
+    ///Both CLKOUTPD and OSCPD are cleared, so the resulting command can't request a clock output
+    ///while the oscillator is powered down. Other fields are kept.
+    #[must_use]
+    pub const fn clkout_enabled(self) -> PowerDown {
+        let data = self.data & !(0b11 << 5);
+        self.with_data(data)
+    }
+    ///Power down CLKOUT, other fields are kept.
+    ///
+    ///Then the oscillator can be powered down too, with [`oscpd_safe`](PowerDown::oscpd_safe).
+    #[must_use]
+    pub const fn clkout_disabled(self) -> PowerDown<ClkoutPoweredDown> {
+        let data = self.data | 0b1 << 6;
+        self.with_data(data)
+    }
+    pub const fn lineinpd(self) -> Lineinpd<CLKOUT> {
         Lineinpd { cmd: self }
     }
-    pub fn micpd(self) -> Micpd {
+    pub const fn micpd(self) -> Micpd<CLKOUT> {
         Micpd { cmd: self }
     }
-    pub fn adcpd(self) -> Adcpd {
+    pub const fn adcpd(self) -> Adcpd<CLKOUT> {
         Adcpd { cmd: self }
     }
-    pub fn dacpd(self) -> Dacpd {
+    pub const fn dacpd(self) -> Dacpd<CLKOUT> {
         Dacpd { cmd: self }
     }
-    pub fn outpd(self) -> Outpd {
+    pub const fn outpd(self) -> Outpd<CLKOUT> {
         Outpd { cmd: self }
     }
-    pub fn oscpd(self) -> Oscpd {
+    pub const fn oscpd(self) -> Oscpd<CLKOUT> {
         Oscpd { cmd: self }
     }
-    pub fn clkoutpd(self) -> Clkoutpd {
+    pub const fn clkoutpd(self) -> Clkoutpd<CLKOUT> {
         Clkoutpd { cmd: self }
     }
-    pub fn poweroff(self) -> Poweroff {
+    pub const fn poweroff(self) -> Poweroff<CLKOUT> {
         Poweroff { cmd: self }
     }
-    pub fn into_command(self) -> Command<()> {
+    pub const fn into_command(self) -> Command<()> {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()> {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
@@ -61,11 +222,209 @@ impl PowerDown {
     }
 }
 
-impl_toggle_writer!(Lineinpd, PowerDown, 0);
-impl_toggle_writer!(Micpd, PowerDown, 1);
-impl_toggle_writer!(Adcpd, PowerDown, 2);
-impl_toggle_writer!(Dacpd, PowerDown, 3);
-impl_toggle_writer!(Outpd, PowerDown, 4);
-impl_toggle_writer!(Oscpd, PowerDown, 5);
-impl_toggle_writer!(Clkoutpd, PowerDown, 6);
-impl_toggle_writer!(Poweroff, PowerDown, 7);
+impl PowerDown<ClkoutPoweredDown> {
+    ///Power down the oscillator, CLKOUT being known to be powered down.
+    #[must_use]
+    pub const fn oscpd_safe(self) -> Self {
+        let data = self.data | 0b1 << 5;
+        self.with_data(data)
+    }
+}
+
+impl_toggle_writer!(Lineinpd<CLKOUT>, PowerDown<CLKOUT>, 0);
+impl_toggle_writer!(Micpd<CLKOUT>, PowerDown<CLKOUT>, 1);
+impl_toggle_writer!(Adcpd<CLKOUT>, PowerDown<CLKOUT>, 2);
+impl_toggle_writer!(Dacpd<CLKOUT>, PowerDown<CLKOUT>, 3);
+impl_toggle_writer!(Outpd<CLKOUT>, PowerDown<CLKOUT>, 4);
+
+/// Field writer. Power down the oscillator, which also stops CLKOUT.
+///
+/// Powering down is only offered once CLKOUT is known to be powered down.
+pub struct Oscpd<CLKOUT> {
+    cmd: PowerDown<CLKOUT>,
+}
+
+impl<CLKOUT> Oscpd<CLKOUT> {
+    impl_clear_bit!(PowerDown<CLKOUT>, 5);
+    impl_disable!(PowerDown<CLKOUT>, 5);
+}
+
+impl Oscpd<ClkoutPoweredDown> {
+    impl_bit!(PowerDown<ClkoutPoweredDown>, 5);
+    impl_set_bit!(PowerDown<ClkoutPoweredDown>, 5);
+    impl_enable!(PowerDown<ClkoutPoweredDown>, 5);
+}
+
+impl Oscpd<AnyClkout> {
+    ///Writes raw bit to the field.
+    ///
+    /// # Safety
+    ///
+    /// CLKOUT may be powered, powering down the oscillator stops it. A chip clocked from CLKOUT
+    /// would lose its clock.
+    #[must_use]
+    pub const unsafe fn bit(mut self, value: bool) -> PowerDown {
+        self.cmd.data = self.cmd.data & !(1 << 5) | (value as u16) << 5;
+        self.cmd
+    }
+}
+impl_toggle_writer!(Poweroff<CLKOUT>, PowerDown<CLKOUT>, 7);
+
+/// Field writer. Power down CLKOUT. Change the CLKOUT marker of the builder.
+pub struct Clkoutpd<CLKOUT> {
+    cmd: PowerDown<CLKOUT>,
+}
+
+impl<CLKOUT> Clkoutpd<CLKOUT> {
+    const fn write<C>(self, value: bool) -> PowerDown<C> {
+        let data = self.cmd.data & !(1 << 6) | (value as u16) << 6;
+        self.cmd.with_data(data)
+    }
+    ///Writes raw bit to the field.
+    #[must_use]
+    pub const fn bit(self, value: bool) -> PowerDown {
+        self.write(value)
+    }
+    /// Sets the bit field.
+    #[must_use]
+    pub const fn set_bit(self) -> PowerDown<ClkoutPoweredDown> {
+        self.write(true)
+    }
+    /// Clears the bit field.
+    #[must_use]
+    pub const fn clear_bit(self) -> PowerDown {
+        self.write(false)
+    }
+    #[must_use]
+    pub const fn enable(self) -> PowerDown<ClkoutPoweredDown> {
+        self.write(true)
+    }
+    #[must_use]
+    pub const fn disable(self) -> PowerDown {
+        self.write(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn clkout_enabled() {
+        let cmd = power_down().all().clkout_enabled();
+        let expected = 0b110 << 9 | 0b1001_1111;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = power_down().none().clkout_disabled().oscpd_safe();
+        let expected = 0b110 << 9 | 0b0110_0000;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn presets() {
+        let cmd = PowerDown::power_up_analog();
+        let expected = 0b110 << 9 | 0b0001_0000;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = PowerDown::power_down_analog();
+        let expected = 0b110 << 9 | 0b0001_1111;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn all_and_none() {
+        let cmd = power_down().all();
+        let expected = 0b110 << 9 | 0xFF;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = power_down().none();
+        let expected = 0b110 << 9;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn from_command() {
+        let cmd = power_down().none().into_command();
+        let test = PowerDown::from_command(cmd).outpd().enable();
+        let expected = 0b110 << 9 | 0b0001_0000;
+        assert!(
+            test.data == expected,
+            "Got {:#b},expected {:#b}",
+            test.data,
+            expected
+        );
+    }
+    #[test]
+    fn oscpd_safe() {
+        let cmd = power_down().clkoutpd().enable().oscpd_safe();
+        let expected = 0b110 << 9 | 0b1111_1111;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn safe_shutdown() {
+        use crate::command::digital_audio_path::digital_audio_path;
+        let path = digital_audio_path().dacmu().disable().adchpd().enable();
+        let power = power_down().none();
+        let test = PowerDown::safe_shutdown(path, power).map(u16::from);
+        let expected = [
+            0b101 << 9 | 0b1001,
+            0b110 << 9 | 0b0001_0000,
+            0b110 << 9 | 0b0001_1111,
+            0b110 << 9 | 0b1111_1111,
+        ];
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+    }
+    #[test]
+    fn safe_shutdown_keeps_powered_down_blocks() {
+        use crate::command::digital_audio_path::digital_audio_path;
+        let path = digital_audio_path().dacmu().disable();
+        let power = power_down().none().micpd().enable().clkoutpd().enable();
+        let test = PowerDown::safe_shutdown(path, power).map(u16::from);
+        let expected = [
+            0b101 << 9 | 0b1000,
+            0b110 << 9 | 0b0101_0010,
+            0b110 << 9 | 0b0101_1111,
+            0b110 << 9 | 0b1111_1111,
+        ];
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+    }
+    #[test]
+    fn power_down_from_base() {
+        let cmd = power_down_from(0b0000_0100).adcpd().disable();
+        let expected = 0b110 << 9;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+}
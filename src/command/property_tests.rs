@@ -0,0 +1,556 @@
+//! Property tests checking builders only write the bits of the targeted field.
+
+use super::*;
+use quickcheck::quickcheck;
+
+///Check `word` keeps the address of `default` and only differs from it inside `mask`, where it
+///holds `field`.
+fn field_only(default: Command<()>, word: Command<()>, mask: u16, field: u16) -> bool {
+    let (default, word) = (default.into_raw(), word.into_raw());
+    word >> 9 == default >> 9 && (word ^ default) & !mask == 0 && word & mask == field & mask
+}
+
+fn bit(value: bool, pos: u8) -> u16 {
+    (value as u16) << pos
+}
+
+#[test]
+fn line_in() {
+    fn prop(vol: u8, mute: bool, both: bool) -> bool {
+        let left = left_line_in().into_command();
+        let right = right_line_in().into_command();
+        field_only(
+            left,
+            left_line_in().invol().bits(vol).into_command(),
+            0x1F,
+            vol as u16,
+        ) && field_only(
+            right,
+            right_line_in().invol().bits(vol).into_command(),
+            0x1F,
+            vol as u16,
+        ) && field_only(
+            left,
+            left_line_in().inmute().bit(mute).into_command(),
+            1 << 7,
+            bit(mute, 7),
+        ) && field_only(
+            left,
+            left_line_in().inboth().bit(both).into_command(),
+            1 << 8,
+            bit(both, 8),
+        ) && field_only(
+            right,
+            right_line_in().inboth().bit(both).into_command(),
+            1 << 8,
+            bit(both, 8),
+        )
+    }
+    quickcheck(prop as fn(u8, bool, bool) -> bool);
+}
+
+#[test]
+fn headphone_out() {
+    fn prop(vol: u8, zcen: bool, both: bool) -> bool {
+        let left = left_headphone_out().into_command();
+        let right = right_headphone_out().into_command();
+        field_only(
+            left,
+            left_headphone_out().hpvol().bits(vol).into_command(),
+            0x7F,
+            vol as u16,
+        ) && field_only(
+            right,
+            right_headphone_out().hpvol().bits(vol).into_command(),
+            0x7F,
+            vol as u16,
+        ) && field_only(
+            left,
+            left_headphone_out().zcen().bit(zcen).into_command(),
+            1 << 7,
+            bit(zcen, 7),
+        ) && field_only(
+            left,
+            left_headphone_out().hpboth().bit(both).into_command(),
+            1 << 8,
+            bit(both, 8),
+        ) && field_only(
+            right,
+            right_headphone_out().hpboth().bit(both).into_command(),
+            1 << 8,
+            bit(both, 8),
+        )
+    }
+    quickcheck(prop as fn(u8, bool, bool) -> bool);
+}
+
+#[test]
+fn analogue_audio_path() {
+    fn prop(value: bool, att: u8) -> bool {
+        let d = super::analogue_audio_path().into_command();
+        let b = super::analogue_audio_path;
+        field_only(
+            d,
+            b().micboost().bit(value).into_command(),
+            1,
+            bit(value, 0),
+        ) && field_only(
+            d,
+            b().mutemic().bit(value).into_command(),
+            1 << 1,
+            bit(value, 1),
+        ) && field_only(
+            d,
+            b().insel().bit(value).into_command(),
+            1 << 2,
+            bit(value, 2),
+        ) && field_only(
+            d,
+            b().bypass().bit(value).into_command(),
+            1 << 3,
+            bit(value, 3),
+        ) && field_only(
+            d,
+            b().dacsel().bit(value).into_command(),
+            1 << 4,
+            bit(value, 4),
+        ) && field_only(
+            d,
+            b().sidetone().bit(value).into_command(),
+            1 << 5,
+            bit(value, 5),
+        ) && field_only(
+            d,
+            b().sideatt().bits(att).into_command(),
+            0b11 << 6,
+            (att as u16) << 6,
+        )
+    }
+    quickcheck(prop as fn(bool, u8) -> bool);
+}
+
+#[test]
+fn digital_audio_path() {
+    fn prop(value: bool, deemp: u8) -> bool {
+        let d = super::digital_audio_path().into_command();
+        let b = super::digital_audio_path;
+        field_only(d, b().adchpd().bit(value).into_command(), 1, bit(value, 0))
+            && field_only(
+                d,
+                b().deemp().bits(deemp).into_command(),
+                0b11 << 1,
+                (deemp as u16) << 1,
+            )
+            && field_only(
+                d,
+                b().dacmu().bit(value).into_command(),
+                1 << 3,
+                bit(value, 3),
+            )
+            && field_only(
+                d,
+                b().hpor().bit(value).into_command(),
+                1 << 4,
+                bit(value, 4),
+            )
+    }
+    quickcheck(prop as fn(bool, u8) -> bool);
+}
+
+#[test]
+fn power_down() {
+    fn prop(value: bool) -> bool {
+        let d = super::power_down().into_command();
+        let b = super::power_down;
+        let clkoutpd = if value {
+            b().clkoutpd().set_bit().into_command()
+        } else {
+            b().clkoutpd().clear_bit().into_command()
+        };
+        field_only(
+            d,
+            b().lineinpd().bit(value).into_command(),
+            1,
+            bit(value, 0),
+        ) && field_only(
+            d,
+            b().micpd().bit(value).into_command(),
+            1 << 1,
+            bit(value, 1),
+        ) && field_only(
+            d,
+            b().adcpd().bit(value).into_command(),
+            1 << 2,
+            bit(value, 2),
+        ) && field_only(
+            d,
+            b().dacpd().bit(value).into_command(),
+            1 << 3,
+            bit(value, 3),
+        ) && field_only(
+            d,
+            b().outpd().bit(value).into_command(),
+            1 << 4,
+            bit(value, 4),
+        ) && field_only(
+            d,
+            unsafe { b().oscpd().bit(value) }.into_command(),
+            1 << 5,
+            bit(value, 5),
+        ) && field_only(d, clkoutpd, 1 << 6, bit(value, 6))
+            && field_only(
+                d,
+                b().poweroff().bit(value).into_command(),
+                1 << 7,
+                bit(value, 7),
+            )
+    }
+    quickcheck(prop as fn(bool) -> bool);
+}
+
+#[test]
+fn digital_audio_interface() {
+    fn prop(value: bool, format: u8, iwl: u8) -> bool {
+        let d = super::digital_audio_interface().into_command();
+        let b = super::digital_audio_interface;
+        field_only(
+            d,
+            b().format().bits(format).into_command(),
+            0b11,
+            format as u16,
+        ) && field_only(
+            d,
+            b().iwl().bits(iwl).into_command(),
+            0b11 << 2,
+            (iwl as u16) << 2,
+        ) && field_only(
+            d,
+            b().lrp().bit(value).into_command(),
+            1 << 4,
+            bit(value, 4),
+        ) && field_only(
+            d,
+            b().lrswap().bit(value).into_command(),
+            1 << 5,
+            bit(value, 5),
+        ) && field_only(d, b().ms().bit(value).into_command(), 1 << 6, bit(value, 6))
+            && field_only(
+                d,
+                b().bclkinv().bit(value).into_command(),
+                1 << 7,
+                bit(value, 7),
+            )
+    }
+    quickcheck(prop as fn(bool, u8, u8) -> bool);
+}
+
+#[test]
+fn sampling() {
+    fn prop(value: bool) -> bool {
+        let d = super::sampling().into_command();
+        let b = super::sampling;
+        field_only(
+            d,
+            b().clkidiv2().bit(value).into_command(),
+            1 << 6,
+            bit(value, 6),
+        ) && field_only(
+            d,
+            b().clkodiv2().bit(value).into_command(),
+            1 << 7,
+            bit(value, 7),
+        )
+    }
+    quickcheck(prop as fn(bool) -> bool);
+}
+
+#[test]
+fn active_control() {
+    fn prop(value: bool) -> bool {
+        let d = super::active_control().into_command();
+        let word = if value {
+            super::active_control().active().into_command()
+        } else {
+            super::active_control().inactive().into_command()
+        };
+        field_only(d, word, 1, bit(value, 0))
+    }
+    quickcheck(prop as fn(bool) -> bool);
+}
+
+#[test]
+fn typed_writers() {
+    use analogue_audio_path::{DacselV, InselV, MicboostV, SideAttV, SideAttdB};
+    use digital_audio_path::DeempV;
+    use headphone_out::HpVoldB;
+    use line_in::InVoldB;
+    fn prop(value: bool, att: u8, vol: u8, deemp: u8) -> bool {
+        let d = super::analogue_audio_path().into_command();
+        let b = super::analogue_audio_path;
+        let dacsel = if value {
+            b().dacsel().select()
+        } else {
+            b().dacsel().deselect()
+        };
+        let side_att = [
+            SideAttV::N6dB,
+            SideAttV::N9dB,
+            SideAttV::N12dB,
+            SideAttV::N15dB,
+        ][att as usize % 4];
+        let deemp_v =
+            [DeempV::Disable, DeempV::F32k, DeempV::F44k1, DeempV::F48k][deemp as usize % 4];
+        let hp_vol = vol.clamp(HpVoldB::MIN.into_raw(), HpVoldB::MAX.into_raw());
+        field_only(d, dacsel.into_command(), 1 << 4, bit(value, 4))
+            && field_only(
+                d,
+                b().dacsel().variant(DacselV::from(value)).into_command(),
+                1 << 4,
+                bit(value, 4),
+            )
+            && field_only(
+                d,
+                b().insel().variant(InselV::from(value)).into_command(),
+                1 << 2,
+                bit(value, 2),
+            )
+            && field_only(
+                d,
+                b().micboost()
+                    .variant(MicboostV::from(value))
+                    .into_command(),
+                1,
+                bit(value, 0),
+            )
+            && field_only(
+                d,
+                b().sideatt().db(SideAttdB::from_raw(att)).into_command(),
+                0b11 << 6,
+                (att.min(0b11) as u16) << 6,
+            )
+            && field_only(
+                d,
+                b().sideatt().variant(side_att).into_command(),
+                0b11 << 6,
+                (att as u16 % 4) << 6,
+            )
+            && field_only(
+                super::digital_audio_path().into_command(),
+                super::digital_audio_path()
+                    .deemp()
+                    .variant(deemp_v)
+                    .into_command(),
+                0b11 << 1,
+                (deemp as u16 % 4) << 1,
+            )
+            && field_only(
+                left_line_in().into_command(),
+                left_line_in()
+                    .invol()
+                    .db(InVoldB::from_raw(vol))
+                    .into_command(),
+                0x1F,
+                vol.min(0x1F) as u16,
+            )
+            && field_only(
+                right_headphone_out().into_command(),
+                right_headphone_out()
+                    .hpvol()
+                    .db(HpVoldB::from_raw(vol))
+                    .into_command(),
+                0x7F,
+                hp_vol as u16,
+            )
+    }
+    quickcheck(prop as fn(bool, u8, u8, u8) -> bool);
+}
+
+#[test]
+fn sampling_mode_fields() {
+    use sampling::state_marker::{SrInvalid, SrTable};
+    use sampling::Sampling;
+    ///Write SR both unchecked and checked, the checked write must match when it succeeds.
+    fn sr<MODE, BOSR>(builder: Sampling<(MODE, BOSR, SrInvalid)>, sr: u8) -> Option<Command<()>>
+    where
+        (MODE, BOSR): SrTable,
+    {
+        let raw = unsafe { builder.sr().bits(sr) }.into_command();
+        match builder.sr().try_bits(sr) {
+            Ok(checked) if checked.into_command() == raw => Some(raw),
+            Ok(_) => None,
+            Err(_) => (sr >= 16 || <(MODE, BOSR)>::VALID_SR & 1 << sr == 0).then_some(raw),
+        }
+    }
+    fn prop(usb: bool, bosr: bool, value: u8) -> bool {
+        let d = super::sampling().into_command();
+        let b = super::sampling;
+        let word = match (usb, bosr) {
+            (false, false) => sr(b().usb_normal().normal().bosr().clear_bit(), value),
+            (false, true) => sr(b().usb_normal().normal().bosr().set_bit(), value),
+            (true, false) => sr(b().usb_normal().usb().bosr().clear_bit(), value),
+            (true, true) => sr(b().usb_normal().usb().bosr().set_bit(), value),
+        };
+        let field = bit(usb, 0) | bit(bosr, 1) | (value as u16 & 0xF) << 2;
+        word.is_some_and(|word| field_only(d, word, 0x3F, field))
+    }
+    quickcheck(prop as fn(bool, bool, u8) -> bool);
+}
+
+#[test]
+fn sample_rate_hz() {
+    use sampling::{Mclk, Mclk12M};
+    fn prop(adc: u8, dac: u8, div: bool) -> bool {
+        let rates = Mclk12M::SUPPORTED;
+        let adc = rates[adc as usize % rates.len()].0;
+        let dac = rates[dac as usize % rates.len()].1;
+        let b = sampling_with_mclk(Mclk12M).clkodiv2().bit(div);
+        let d =
+            unsafe { Command::from_raw((crate::registers::SAMPLING as u16) << 9 | bit(div, 7)) };
+        let preset = rates.iter().find(|rate| rate.0 == adc && rate.1 == dac);
+        match (b.sample_rate().try_adc_dac(adc, dac), preset) {
+            (Ok(word), Some(preset)) => field_only(d, word.into_command(), 0x3F, preset.2 as u16),
+            (Err(_), None) => true,
+            _ => false,
+        }
+    }
+    quickcheck(prop as fn(u8, u8, bool) -> bool);
+}
+
+///Check a chain of raw writes against a model of the register, `fields` being the
+///`(position, width)` of each field targeted by `write`.
+fn chained<B: Copy>(
+    default: B,
+    fields: &[(u8, u8)],
+    write: fn(B, usize, u8) -> B,
+    into_command: fn(B) -> Command<()>,
+    writes: &[(u8, u8)],
+) -> bool {
+    let mut model = into_command(default).into_raw();
+    let mut builder = default;
+    for &(field, value) in writes {
+        let index = field as usize % fields.len();
+        let (pos, width) = fields[index];
+        let mask = !(!0u16 << width) << pos;
+        model = model & !mask | (value as u16) << pos & mask;
+        builder = write(builder, index, value);
+    }
+    into_command(builder).into_raw() == model
+}
+
+#[test]
+fn chained_writes() {
+    use analogue_audio_path::AnalogueAudioPath;
+    use digital_audio_interface::state_marker::{AnyFormat, AnyMode};
+    use digital_audio_interface::DigitalAudioInterface;
+    use digital_audio_path::DigitalAudioPath;
+    use headphone_out::LeftHeadphoneOut;
+    use line_in::RightLineIn;
+    use power_down::PowerDown;
+    use std::vec::Vec;
+    fn prop(writes: Vec<(u8, u8)>) -> bool {
+        let line_in = chained(
+            right_line_in(),
+            &[(0, 5), (7, 1), (8, 1)],
+            |b: RightLineIn, field, value| match field {
+                0 => b.invol().bits(value),
+                1 => b.inmute().bit(value & 1 != 0),
+                _ => b.inboth().bit(value & 1 != 0),
+            },
+            RightLineIn::into_command,
+            &writes,
+        );
+        let headphone_out = chained(
+            left_headphone_out(),
+            &[(0, 7), (7, 1), (8, 1)],
+            |b: LeftHeadphoneOut, field, value| match field {
+                0 => b.hpvol().bits(value),
+                1 => b.zcen().bit(value & 1 != 0),
+                _ => b.hpboth().bit(value & 1 != 0),
+            },
+            LeftHeadphoneOut::into_command,
+            &writes,
+        );
+        let analogue_audio_path = chained(
+            super::analogue_audio_path(),
+            &[(0, 1), (1, 1), (2, 1), (3, 1), (4, 1), (5, 1), (6, 2)],
+            |b: AnalogueAudioPath, field, value| {
+                let v = value & 1 != 0;
+                match field {
+                    0 => b.micboost().bit(v),
+                    1 => b.mutemic().bit(v),
+                    2 => b.insel().bit(v),
+                    3 => b.bypass().bit(v),
+                    4 => b.dacsel().bit(v),
+                    5 => b.sidetone().bit(v),
+                    _ => b.sideatt().bits(value),
+                }
+            },
+            AnalogueAudioPath::into_command,
+            &writes,
+        );
+        let digital_audio_path = chained(
+            super::digital_audio_path(),
+            &[(0, 1), (1, 2), (3, 1), (4, 1)],
+            |b: DigitalAudioPath, field, value| match field {
+                0 => b.adchpd().bit(value & 1 != 0),
+                1 => b.deemp().bits(value),
+                2 => b.dacmu().bit(value & 1 != 0),
+                _ => b.hpor().bit(value & 1 != 0),
+            },
+            DigitalAudioPath::into_command,
+            &writes,
+        );
+        let power_down = chained(
+            super::power_down(),
+            &[
+                (0, 1),
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (4, 1),
+                (5, 1),
+                (6, 1),
+                (7, 1),
+            ],
+            |b: PowerDown, field, value| {
+                let v = value & 1 != 0;
+                match field {
+                    0 => b.lineinpd().bit(v),
+                    1 => b.micpd().bit(v),
+                    2 => b.adcpd().bit(v),
+                    3 => b.dacpd().bit(v),
+                    4 => b.outpd().bit(v),
+                    5 => unsafe { b.oscpd().bit(v) },
+                    6 => b.clkoutpd().bit(v),
+                    _ => b.poweroff().bit(v),
+                }
+            },
+            PowerDown::into_command,
+            &writes,
+        );
+        let digital_audio_interface = chained(
+            DigitalAudioInterface::from_command(super::digital_audio_interface().into_command()),
+            &[(0, 2), (2, 2), (4, 1), (5, 1), (6, 1), (7, 1)],
+            |b: DigitalAudioInterface<(AnyFormat, AnyMode)>, field, value| {
+                let v = value & 1 != 0;
+                match field {
+                    0 => b.format().bits(value),
+                    1 => b.iwl().bits(value),
+                    2 => b.lrp().bit(v),
+                    3 => b.lrswap().bit(v),
+                    4 => b.ms().bit(v),
+                    _ => b.bclkinv().bit(v),
+                }
+            },
+            DigitalAudioInterface::into_command,
+            &writes,
+        );
+        line_in
+            && headphone_out
+            && analogue_audio_path
+            && digital_audio_path
+            && power_down
+            && digital_audio_interface
+    }
+    quickcheck(prop as fn(Vec<(u8, u8)>) -> bool);
+}
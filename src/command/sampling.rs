@@ -12,6 +12,9 @@
 //!  - `Mclk16M9344` for a 16.9344 MHz master clock
 //!  - `Mclk12M` for a 12MHz master clock, correspond to USB mode.
 //!
+//! The frequency of a marker is available as `Mclk::FREQ_HZ`, which is handy for clock
+//! computations in generic code.
+//!
 //! You also don't write directly to USB/NORMAL, BOSR, or SR fields. Instead, you use a *virtual*
 //! SampleRate field that do it for you.
 //!
@@ -49,6 +52,16 @@
 //! let cmd = cmd.into_command();
 //! ```
 //!
+//! # Master clock divider
+//! Both styles track the CLKIDIV2 bit in the builder type, the resulting division factor is
+//! available with `clkidiv_factor`.
+//!
+//! Master clock markers give the internal master clock, after CLKIDIV2. No sampling rate requires
+//! the divider, 88.2kHz and 96kHz included, so it only depends on the MCLK pin frequency: it's
+//! needed when the pin frequency is twice the internal one, for example a 24.576MHz MCLK for
+//! `Mclk12M288`. `mclk_input_hz` sets it from the MCLK pin frequency, keeping the divider in the
+//! type of each [`SamplingClkDiv`] variant. It can still be overridden with `clkidiv2`.
+//!
 //! # Safety and coherence
 //! To guarantee safety and coherence, some manipulation are enforced or prohibited.
 //!
@@ -81,8 +94,9 @@
 //! ```
 //!
 //!
-#![allow(clippy::new_without_default)]
 
+use crate::interface::Frame;
+use crate::registers;
 use crate::Command;
 use core::marker::PhantomData;
 
@@ -105,52 +119,271 @@ pub mod state_marker {
     ///
     /// `Sampling` configuration marked with this can not produce a command.
     pub struct SrInvalid;
+    /// Marker used to indicate CLKIDIV2 bit is clear, the master clock isn't divided.
+    pub struct Div1;
+    /// Marker used to indicate CLKIDIV2 bit is set, the master clock is divided by 2.
+    pub struct Div2;
+    /// Marker used to indicate CLKIDIV2 bit is only known at runtime.
+    pub struct AnyDiv;
+
+    /// Master clock division applied by CLKIDIV2.
+    pub trait ClkDiv {
+        /// Division factor.
+        const FACTOR: u32;
+    }
+    impl ClkDiv for Div1 {
+        const FACTOR: u32 = 1;
+    }
+    impl ClkDiv for Div2 {
+        const FACTOR: u32 = 2;
+    }
+
+    /// Valid SR values for a USB/NORMAL and BOSR markers combination.
+    pub trait SrTable {
+        /// Bit `n` is set when the SR value `n` is valid.
+        const VALID_SR: u16;
+    }
+    impl<BOSR> SrTable for (Normal, BOSR) {
+        const VALID_SR: u16 = 0b1000_1111_1100_1111;
+    }
+    impl SrTable for (Usb, BosrClear) {
+        const VALID_SR: u16 = 0b0000_0000_1100_1111;
+    }
+    impl SrTable for (Usb, BosrSet) {
+        const VALID_SR: u16 = 0b1000_1111_0000_0000;
+    }
+
+    mod sealed {
+        pub trait Sealed {}
+        impl Sealed for super::SrValid {}
+    }
+
+    /// Implemented by [`SrValid`] only, it's the bound required to produce a command.
+    ///
+    /// ```compile_fail
+    /// # use wm8731_alt::prelude::*;
+    /// # use wm8731_alt::command::sampling::Mclk12M288;
+    /// // sample rate not set
+    /// let cmd = sampling_with_mclk(Mclk12M288).into_command();
+    /// ```
+    #[diagnostic::on_unimplemented(
+        message = "sample_rate must be set before building the command",
+        label = "the sampling rate of this builder isn't set",
+        note = "call `.sample_rate()` then a rate method, or `.sr()` then a `sr_*` method, before `into_command`"
+    )]
+    pub trait SrIsValid: sealed::Sealed {}
+    impl SrIsValid for SrValid {}
 }
 
 use state_marker::*;
 
 /// Builder for sampling command.
-#[derive(Debug, Eq, PartialEq)]
-pub struct Sampling<T> {
+///
+/// `DIV` track the CLKIDIV2 bit, see [`ClkDiv`].
+#[derive(Eq, PartialEq)]
+pub struct Sampling<T, DIV = Div1> {
     data: u16,
     t: PhantomData<T>,
+    div: PhantomData<DIV>,
 }
 
-impl<T> Copy for Sampling<T> {}
+impl<T, DIV> core::fmt::Debug for Sampling<T, DIV> {
+    ///Print the decoded register rather than the raw data.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&crate::decode::decode(self.data), f)
+    }
+}
+
+impl<T, DIV> Copy for Sampling<T, DIV> {}
 
-impl<T> Clone for Sampling<T> {
+impl<T, DIV> Clone for Sampling<T, DIV> {
     fn clone(&self) -> Self {
         *self
     }
 }
 
+impl<MCLK, SR, DIV> From<Sampling<(MCLK, SR), DIV>> for Frame
+where
+    SR: SrIsValid,
+{
+    fn from(builder: Sampling<(MCLK, SR), DIV>) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl<MODE, BOSR, SR, DIV> From<Sampling<(MODE, BOSR, SR), DIV>> for Frame
+where
+    SR: SrIsValid,
+{
+    fn from(builder: Sampling<(MODE, BOSR, SR), DIV>) -> Frame {
+        builder.into_command().into()
+    }
+}
+
+impl Default for Sampling<(Normal, BosrClear, SrValid)> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 //common to both method it's always safe to manipulate those fields
-impl<T> Sampling<T> {
-    pub fn clkidiv2(self) -> Clkidiv2<T> {
+impl<T, DIV> Sampling<T, DIV> {
+    pub const fn clkidiv2(self) -> Clkidiv2<T, DIV> {
         Clkidiv2 { cmd: self }
     }
-    pub fn clkodiv2(self) -> Clkodiv2<T> {
+    pub const fn clkodiv2(self) -> Clkodiv2<T, DIV> {
         Clkodiv2 { cmd: self }
     }
 }
 
+impl<T, DIV> Sampling<T, DIV>
+where
+    DIV: ClkDiv,
+{
+    ///Master clock division factor applied by CLKIDIV2.
+    pub const fn clkidiv_factor(&self) -> u32 {
+        DIV::FACTOR
+    }
+}
+
 ///Marker indicating use of 12.288Mhz internal master clock (normal mode).
 pub struct Mclk12M288;
-impl Mclk for Mclk12M288 {}
+impl Mclk for Mclk12M288 {
+    const FREQ_HZ: u32 = 12_288_000;
+    const SUPPORTED: &'static [(u32, u32, u8)] = &[
+        (48_000, 48_000, 0b000000),
+        (48_000, 8_000, 0b000100),
+        (8_000, 48_000, 0b001000),
+        (8_000, 8_000, 0b001100),
+        (32_000, 32_000, 0b011000),
+        (96_000, 96_000, 0b011100),
+    ];
+}
 ///Marker indicating use of 18.432Mhz internal master clock (normal mode).
 pub struct Mclk18M432;
-impl Mclk for Mclk18M432 {}
+impl Mclk for Mclk18M432 {
+    const FREQ_HZ: u32 = 18_432_000;
+    const SUPPORTED: &'static [(u32, u32, u8)] = &[
+        (48_000, 48_000, 0b000010),
+        (48_000, 8_000, 0b000110),
+        (8_000, 48_000, 0b001010),
+        (8_000, 8_000, 0b001110),
+        (32_000, 32_000, 0b011010),
+        (96_000, 96_000, 0b011110),
+    ];
+}
 ///Marker indicating use of 11.2896Mhz internal master clock (normal mode).
 pub struct Mclk11M2896;
-impl Mclk for Mclk11M2896 {}
+impl Mclk for Mclk11M2896 {
+    const FREQ_HZ: u32 = 11_289_600;
+    const SUPPORTED: &'static [(u32, u32, u8)] = &[
+        (44_100, 44_100, 0b100000),
+        (44_100, 8_000, 0b100100),
+        (8_000, 44_100, 0b101000),
+        (8_000, 8_000, 0b101100),
+        (88_200, 88_200, 0b111100),
+    ];
+}
 ///Marker indicating use of 16.9344Mhz internal master clock (normal mode).
 pub struct Mclk16M9344;
-impl Mclk for Mclk16M9344 {}
+impl Mclk for Mclk16M9344 {
+    const FREQ_HZ: u32 = 16_934_400;
+    const SUPPORTED: &'static [(u32, u32, u8)] = &[
+        (44_100, 44_100, 0b100010),
+        (44_100, 8_000, 0b100110),
+        (8_000, 44_100, 0b101010),
+        (8_000, 8_000, 0b101110),
+        (88_200, 88_200, 0b111110),
+    ];
+}
 ///Marker indicating use of 12Mhz internal master clock (USB mode).
 pub struct Mclk12M;
-impl Mclk for Mclk12M {}
+impl Mclk for Mclk12M {
+    const FREQ_HZ: u32 = 12_000_000;
+    const SUPPORTED: &'static [(u32, u32, u8)] = &[
+        (48_000, 48_000, 0b000001),
+        (44_100, 44_100, 0b100011),
+        (48_000, 8_000, 0b000101),
+        (44_100, 8_000, 0b100111),
+        (8_000, 48_000, 0b001001),
+        (8_000, 44_100, 0b101011),
+        (8_000, 8_000, 0b001101),
+        (8_000, 8_000, 0b101111),
+        (32_000, 32_000, 0b011001),
+        (96_000, 96_000, 0b011101),
+        (88_200, 88_200, 0b111111),
+    ];
+}
 /// Marker trait to say a marker correspond to a master clock value.
-pub trait Mclk {}
+pub trait Mclk {
+    /// Frequency of the master clock in Hz.
+    const FREQ_HZ: u32;
+    /// Sampling rates available with this master clock.
+    ///
+    /// Each entry is the nominal ADC rate and DAC rate in Hz, followed by the USB/NORMAL, BOSR
+    /// and SR bits, packed as `SR << 2 | BOSR << 1 | USB/NORMAL`. When several entries have the
+    /// same nominal rates, the exact one comes first.
+    const SUPPORTED: &'static [(u32, u32, u8)];
+
+    /// Return sampling rates available with this master clock, see [`SUPPORTED`](Self::SUPPORTED).
+    fn supported_rates() -> &'static [(u32, u32, u8)] {
+        Self::SUPPORTED
+    }
+}
+
+/// Error returned when a sampling rate is not available with the master clock.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct UnsupportedRate;
+
+/// Error returned when the MCLK pin frequency is neither the master clock frequency nor twice it.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct UnsupportedClock;
+
+/// Error returned when a SR value isn't valid for the USB/NORMAL and BOSR settings.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct InvalidSr;
+
+/// ADC and DAC sampling rates in Hz, for runtime selected rates.
+///
+/// Rates are nominal, e.g. 44.1kHz also stands for the 44.118kHz rate of USB mode.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SampleRateHz {
+    pub adc: u32,
+    pub dac: u32,
+}
+
+impl SampleRateHz {
+    pub const ADC48K_DAC48K: Self = Self::new(48_000, 48_000);
+    pub const ADC48K_DAC8K: Self = Self::new(48_000, 8_000);
+    pub const ADC8K_DAC48K: Self = Self::new(8_000, 48_000);
+    pub const ADC8K_DAC8K: Self = Self::new(8_000, 8_000);
+    pub const ADC32K_DAC32K: Self = Self::new(32_000, 32_000);
+    pub const ADC96K_DAC96K: Self = Self::new(96_000, 96_000);
+    pub const ADC44K1_DAC44K1: Self = Self::new(44_100, 44_100);
+    pub const ADC44K1_DAC8K: Self = Self::new(44_100, 8_000);
+    pub const ADC8K_DAC44K1: Self = Self::new(8_000, 44_100);
+    pub const ADC88K2_DAC88K2: Self = Self::new(88_200, 88_200);
+
+    pub const fn new(adc: u32, dac: u32) -> Self {
+        Self { adc, dac }
+    }
+
+    /// Return the USB/NORMAL, BOSR and SR fields to write for this rate, with the given master
+    /// clock.
+    ///
+    /// When several setups match, the first entry of [`Mclk::SUPPORTED`] is used, that is the exact
+    /// one.
+    pub fn bits<MCLK: Mclk>(self) -> Result<(bool, bool, u8), UnsupportedRate> {
+        match MCLK::SUPPORTED
+            .iter()
+            .find(|&&(adc, dac, _)| adc == self.adc && dac == self.dac)
+        {
+            Some(&(_, _, bits)) => Ok((bits & 0b1 != 0, bits & 0b10 != 0, bits >> 2)),
+            None => Err(UnsupportedRate),
+        }
+    }
+}
 
 /// Instantiate a command builder to set sampling configuration for a particular master clock.
 pub fn sampling_with_mclk<MCLK>(_: MCLK) -> Sampling<(MCLK, SrInvalid)>
@@ -158,243 +391,357 @@ where
     MCLK: Mclk,
 {
     Sampling::<(MCLK, SrInvalid)> {
-        data: 0b1000 << 9,
+        data: (registers::SAMPLING as u16) << 9,
         t: PhantomData::<(MCLK, SrInvalid)>,
+        div: PhantomData::<Div1>,
     }
 }
 
-impl<MCLK, SR> Sampling<(MCLK, SR)>
+impl<MCLK, SR, DIV> Sampling<(MCLK, SR), DIV>
 where
     MCLK: Mclk,
 {
-    pub fn sample_rate(self) -> SampleRate<(MCLK, SR)> {
-        SampleRate::<(MCLK, SR)> { cmd: self }
+    pub const fn sample_rate(self) -> SampleRate<(MCLK, SR), DIV> {
+        SampleRate { cmd: self }
+    }
+    ///Set CLKIDIV2 from the frequency of the MCLK pin, in Hz.
+    ///
+    ///The divider is cleared when the pin frequency is the master clock one, and set when it's
+    ///twice the master clock one. Other frequencies fail. It can still be overridden afterward
+    ///with `clkidiv2`.
+    pub fn mclk_input_hz(
+        self,
+        input_hz: u32,
+    ) -> Result<SamplingClkDiv<(MCLK, SR)>, UnsupportedClock> {
+        if input_hz == MCLK::FREQ_HZ {
+            Ok(SamplingClkDiv::Div1(self.clkidiv2().clear_bit()))
+        } else if input_hz == 2 * MCLK::FREQ_HZ {
+            Ok(SamplingClkDiv::Div2(self.clkidiv2().set_bit()))
+        } else {
+            Err(UnsupportedClock)
+        }
+    }
+}
+
+/// Sampling builder whose CLKIDIV2 bit was selected at runtime, see
+/// [`Sampling::mclk_input_hz`].
+///
+/// Each variant keeps the divider in its type, so [`ClkDiv`] based code still applies.
+pub enum SamplingClkDiv<T> {
+    ///CLKIDIV2 cleared, the MCLK pin is the master clock.
+    Div1(Sampling<T, Div1>),
+    ///CLKIDIV2 set, the MCLK pin is twice the master clock.
+    Div2(Sampling<T, Div2>),
+}
+
+impl<T> core::fmt::Debug for SamplingClkDiv<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SamplingClkDiv::Div1(s) => f.debug_tuple("Div1").field(s).finish(),
+            SamplingClkDiv::Div2(s) => f.debug_tuple("Div2").field(s).finish(),
+        }
+    }
+}
+
+impl<T> Copy for SamplingClkDiv<T> {}
+
+impl<T> Clone for SamplingClkDiv<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> SamplingClkDiv<T> {
+    ///Master clock division factor applied by CLKIDIV2.
+    pub const fn clkidiv_factor(&self) -> u32 {
+        match self {
+            SamplingClkDiv::Div1(s) => s.clkidiv_factor(),
+            SamplingClkDiv::Div2(s) => s.clkidiv_factor(),
+        }
+    }
+}
+
+impl<MCLK, SR> SamplingClkDiv<(MCLK, SR)> {
+    /// Instanciate a command
+    pub const fn into_command(self) -> Command<()>
+    where
+        SR: SrIsValid,
+    {
+        match self {
+            SamplingClkDiv::Div1(s) => s.into_command(),
+            SamplingClkDiv::Div2(s) => s.into_command(),
+        }
+    }
+}
+
+impl<MCLK, SR> From<SamplingClkDiv<(MCLK, SR)>> for Frame
+where
+    SR: SrIsValid,
+{
+    fn from(builder: SamplingClkDiv<(MCLK, SR)>) -> Frame {
+        builder.into_command().into()
     }
 }
 /// Virtual field writer for more meaningful sampling rate setting.
 ///
 /// This actually write USB/NORMAL, BOSR, and SR fields.
-pub struct SampleRate<T> {
-    cmd: Sampling<T>,
+pub struct SampleRate<T, DIV> {
+    cmd: Sampling<T, DIV>,
 }
 
-impl<MCLK, SR> SampleRate<(MCLK, SR)> {
-    unsafe fn bits(mut self, value: u8) -> Sampling<(MCLK, SrValid)> {
+impl<MCLK, SR, DIV> SampleRate<(MCLK, SR), DIV> {
+    const unsafe fn bits(mut self, value: u8) -> Sampling<(MCLK, SrValid), DIV> {
         let mask = !((!0) << 6);
-        self.cmd.data = self.cmd.data & !mask | (value as u16) << 2 & mask;
-        Sampling::<(MCLK, SrValid)> {
+        self.cmd.data = self.cmd.data & !mask | (value as u16) & mask;
+        Sampling::<(MCLK, SrValid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(MCLK, SrValid)>,
+            div: PhantomData::<DIV>,
         }
     }
 }
 
-impl<SR> SampleRate<(Mclk12M288, SR)> {
+impl<MCLK, SR, DIV> SampleRate<(MCLK, SR), DIV>
+where
+    MCLK: Mclk,
+{
+    ///Set the sampling rate from ADC and DAC rates in Hz, for example from a runtime setting.
+    ///
+    ///Rates are nominal, e.g. `44_100` also select the 44.118kHz rate of USB mode. When several
+    ///setups match, the first entry of [`Mclk::SUPPORTED`] is selected, that is the exact one.
+    pub fn try_adc_dac(
+        self,
+        adc_hz: u32,
+        dac_hz: u32,
+    ) -> Result<Sampling<(MCLK, SrValid), DIV>, UnsupportedRate> {
+        self.hz(SampleRateHz::new(adc_hz, dac_hz))
+    }
+    ///Set the sampling rate from a [`SampleRateHz`].
+    pub fn hz(self, rate: SampleRateHz) -> Result<Sampling<(MCLK, SrValid), DIV>, UnsupportedRate> {
+        let (usb, bosr, sr) = rate.bits::<MCLK>()?;
+        Ok(unsafe { self.bits(sr << 2 | (bosr as u8) << 1 | usb as u8) })
+    }
+}
+
+impl<SR, DIV> SampleRate<(Mclk12M288, SR), DIV> {
     ///Set 48khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc48k_dac48k(self) -> Sampling<(Mclk12M288, SrValid)> {
+    pub const fn adc48k_dac48k(self) -> Sampling<(Mclk12M288, SrValid), DIV> {
         unsafe { self.bits(0b000000) }
     }
     ///Set sampling rate of 48khz for ADC and 8khz for DAC.
     #[must_use]
-    pub fn adc48k_dac8k(self) -> Sampling<(Mclk12M288, SrValid)> {
+    pub const fn adc48k_dac8k(self) -> Sampling<(Mclk12M288, SrValid), DIV> {
         unsafe { self.bits(0b000100) }
     }
     ///Set sampling rate of 8khz for ADC and 48khz for DAC.
     #[must_use]
-    pub fn adc8k_dac48k(self) -> Sampling<(Mclk12M288, SrValid)> {
+    pub const fn adc8k_dac48k(self) -> Sampling<(Mclk12M288, SrValid), DIV> {
         unsafe { self.bits(0b001000) }
     }
     ///Set 8khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc8k_dac8k(self) -> Sampling<(Mclk12M288, SrValid)> {
+    pub const fn adc8k_dac8k(self) -> Sampling<(Mclk12M288, SrValid), DIV> {
         unsafe { self.bits(0b001100) }
     }
     ///Set 32khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc32k_dac32k(self) -> Sampling<(Mclk12M288, SrValid)> {
+    pub const fn adc32k_dac32k(self) -> Sampling<(Mclk12M288, SrValid), DIV> {
         unsafe { self.bits(0b011000) }
     }
     ///Set 96khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc96k_dac96k(self) -> Sampling<(Mclk12M288, SrValid)> {
+    pub const fn adc96k_dac96k(self) -> Sampling<(Mclk12M288, SrValid), DIV> {
         unsafe { self.bits(0b011100) }
     }
 }
 
-impl<SR> SampleRate<(Mclk18M432, SR)> {
+impl<SR, DIV> SampleRate<(Mclk18M432, SR), DIV> {
     ///Set 48khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc48k_dac48k(self) -> Sampling<(Mclk18M432, SrValid)> {
+    pub const fn adc48k_dac48k(self) -> Sampling<(Mclk18M432, SrValid), DIV> {
         unsafe { self.bits(0b000010) }
     }
     ///Set sampling rate of 48khz for ADC and 8khz for DAC.
     #[must_use]
-    pub fn adc48k_dac8k(self) -> Sampling<(Mclk18M432, SrValid)> {
+    pub const fn adc48k_dac8k(self) -> Sampling<(Mclk18M432, SrValid), DIV> {
         unsafe { self.bits(0b000110) }
     }
     ///Set sampling rate of 8khz for ADC and 48khz for DAC.
     #[must_use]
-    pub fn adc8k_dac48k(self) -> Sampling<(Mclk18M432, SrValid)> {
+    pub const fn adc8k_dac48k(self) -> Sampling<(Mclk18M432, SrValid), DIV> {
         unsafe { self.bits(0b001010) }
     }
     ///Set 8khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc8k_dac8k(self) -> Sampling<(Mclk18M432, SrValid)> {
+    pub const fn adc8k_dac8k(self) -> Sampling<(Mclk18M432, SrValid), DIV> {
         unsafe { self.bits(0b001110) }
     }
     ///Set 32khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc32k_dac32k(self) -> Sampling<(Mclk18M432, SrValid)> {
+    pub const fn adc32k_dac32k(self) -> Sampling<(Mclk18M432, SrValid), DIV> {
         unsafe { self.bits(0b011010) }
     }
     ///Set 96khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc96k_dac96k(self) -> Sampling<(Mclk18M432, SrValid)> {
+    pub const fn adc96k_dac96k(self) -> Sampling<(Mclk18M432, SrValid), DIV> {
         unsafe { self.bits(0b011110) }
     }
 }
 
-impl<SR> SampleRate<(Mclk11M2896, SR)> {
+impl<SR, DIV> SampleRate<(Mclk11M2896, SR), DIV> {
     ///Set 44.1khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc44k1_dac44k1(self) -> Sampling<(Mclk11M2896, SrValid)> {
+    pub const fn adc44k1_dac44k1(self) -> Sampling<(Mclk11M2896, SrValid), DIV> {
         unsafe { self.bits(0b100000) }
     }
     ///Set sampling rate of 44.1khz for ADC and approximatively 8khz for DAC.
     ///
     ///The actual DAC sampling rate is 8.018kHz
     #[must_use]
-    pub fn adc44k1_dac8k(self) -> Sampling<(Mclk11M2896, SrValid)> {
+    pub const fn adc44k1_dac8k(self) -> Sampling<(Mclk11M2896, SrValid), DIV> {
         unsafe { self.bits(0b100100) }
     }
     ///Set sampling rate of approximatively 8khz for ADC and 44.1khz for DAC.
     ///
     ///The actual ADC sampling rate is 8.018kHz
     #[must_use]
-    pub fn adc8k_dac44k1(self) -> Sampling<(Mclk11M2896, SrValid)> {
+    pub const fn adc8k_dac44k1(self) -> Sampling<(Mclk11M2896, SrValid), DIV> {
         unsafe { self.bits(0b101000) }
     }
     ///Set approximatively 8khz sampling rate for ADC and DAC.
     ///
     ///The actual sampling rate is 8.018kHz
     #[must_use]
-    pub fn adc8k_dac8k(self) -> Sampling<(Mclk11M2896, SrValid)> {
+    pub const fn adc8k_dac8k(self) -> Sampling<(Mclk11M2896, SrValid), DIV> {
         unsafe { self.bits(0b101100) }
     }
     ///Set 88.2khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc88k2_dac88k2(self) -> Sampling<(Mclk11M2896, SrValid)> {
+    pub const fn adc88k2_dac88k2(self) -> Sampling<(Mclk11M2896, SrValid), DIV> {
         unsafe { self.bits(0b111100) }
     }
 }
 
-impl<SR> SampleRate<(Mclk16M9344, SR)> {
+impl<SR, DIV> SampleRate<(Mclk16M9344, SR), DIV> {
     ///Set 44.1khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc44k1_dac44k1(self) -> Sampling<(Mclk16M9344, SrValid)> {
+    pub const fn adc44k1_dac44k1(self) -> Sampling<(Mclk16M9344, SrValid), DIV> {
         unsafe { self.bits(0b100010) }
     }
     ///Set sampling rate of 44.1khz for ADC and approximatively 8khz for DAC.
     ///
     ///The actual DAC sampling rate is 8.018kHz
     #[must_use]
-    pub fn adc44k1_dac8k(self) -> Sampling<(Mclk16M9344, SrValid)> {
+    pub const fn adc44k1_dac8k(self) -> Sampling<(Mclk16M9344, SrValid), DIV> {
         unsafe { self.bits(0b100110) }
     }
     ///Set sampling rate of approximatively 8khz for ADC and 44.1khz for DAC.
     ///
     ///The actual ADC sampling rate is 8.018kHz
     #[must_use]
-    pub fn adc8k_dac44k1(self) -> Sampling<(Mclk16M9344, SrValid)> {
+    pub const fn adc8k_dac44k1(self) -> Sampling<(Mclk16M9344, SrValid), DIV> {
         unsafe { self.bits(0b101010) }
     }
     ///Set approximatively 8khz sampling rate for ADC and DAC.
     ///
     ///The actual sampling rate is 8.018kHz
     #[must_use]
-    pub fn adc8k_dac8k(self) -> Sampling<(Mclk16M9344, SrValid)> {
+    pub const fn adc8k_dac8k(self) -> Sampling<(Mclk16M9344, SrValid), DIV> {
         unsafe { self.bits(0b101110) }
     }
     ///Set 88.2khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc88k2_dac88k2(self) -> Sampling<(Mclk16M9344, SrValid)> {
+    pub const fn adc88k2_dac88k2(self) -> Sampling<(Mclk16M9344, SrValid), DIV> {
         unsafe { self.bits(0b111110) }
     }
 }
 
-impl<SR> SampleRate<(Mclk12M, SR)> {
+impl<SR, DIV> SampleRate<(Mclk12M, SR), DIV> {
     ///Set 48khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc48k_dac48k(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc48k_dac48k(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b000001) }
     }
     ///Set approximatively 44.1khz sampling rate for ADC and DAC.
     ///
     ///The actual sampling rate is 44.118kHz.
     #[must_use]
-    pub fn adc44k1_dac44k1(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc44k1_dac44k1(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b100011) }
     }
     ///Set sampling rate of 48khz for ADC and 8khz for DAC.
     #[must_use]
-    pub fn adc48k_dac8k(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc48k_dac8k(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b000101) }
     }
     ///Set sampling rate of approximatively 44.1khz for ADC and approximatively 8khz for DAC.
     ///
     ///The actual sampling rate are 44.118kHz for the ADC and 8.021kHz for the DAC.
     #[must_use]
-    pub fn adc44k1_dac8k(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc44k1_dac8k(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b100111) }
     }
     ///Set sampling rate of 8khz for ADC and 48khz for DAC.
     #[must_use]
-    pub fn adc8k_dac48k(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc8k_dac48k(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b001001) }
     }
     ///Set sampling rate of approximatively 8khz for ADC and approximatively 44.1khz for DAC.
     ///
     ///The actual sampling rate are 8.021kHz for the ADC and 44.118kHz  for the DAC.
     #[must_use]
-    pub fn adc8k_dac44k1(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc8k_dac44k1(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b101011) }
     }
     ///Set 8khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc8k_dac8k(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc8k_dac8k(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b001101) }
     }
     ///Set approximatively 8khz sampling rate for ADC and DAC.
     ///
     ///The actual sampling rate is 8.021kHz.
     #[must_use]
-    pub fn adc8k_dac8k_bis(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc8k_dac8k_bis(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b101111) }
     }
     ///Set 32khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc32k_dac32k(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc32k_dac32k(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b011001) }
     }
     ///Set 96khz sampling rate for ADC and DAC.
     #[must_use]
-    pub fn adc96k_dac96k(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc96k_dac96k(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b011101) }
     }
     ///Set approximatively 88.2kHz sampling rate for ADC and DAC.
     ///
     ///The actual sampling rate is 88.235kHz.
     #[must_use]
-    pub fn adc88k2_dac88k2(self) -> Sampling<(Mclk12M, SrValid)> {
+    pub const fn adc88k2_dac88k2(self) -> Sampling<(Mclk12M, SrValid), DIV> {
         unsafe { self.bits(0b111111) }
     }
 }
 
 //Once SampleRate have been explicitly set, a valid command can be instantiated
-impl<MCLK> Sampling<(MCLK, SrValid)> {
+impl<MCLK, SR, DIV> Sampling<(MCLK, SR), DIV> {
     /// Instanciate a command
-    pub fn into_command(self) -> Command<()> {
+    pub const fn into_command(self) -> Command<()>
+    where
+        SR: SrIsValid,
+    {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()>
+    where
+        SR: SrIsValid,
+    {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
@@ -403,24 +750,57 @@ impl<MCLK> Sampling<(MCLK, SrValid)> {
 }
 
 /// Instanciate a command builder for sampling configuration.
-pub fn sampling() -> Sampling<(Normal, BosrClear, SrValid)> {
+pub const fn sampling() -> Sampling<(Normal, BosrClear, SrValid)> {
     Sampling::<(Normal, BosrClear, SrValid)>::new()
 }
 
 impl Sampling<(Normal, BosrClear, SrValid)> {
     #[allow(clippy::identity_op)]
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
-            data: 0b1000 << 9 | 0b0000_0000,
+            data: (registers::SAMPLING as u16) << 9 | registers::SAMPLING_DEFAULT,
             t: PhantomData::<(Normal, BosrClear, SrValid)>,
+            div: PhantomData::<Div1>,
         }
     }
 }
 
 //Once sr have been explicitly set, a valid command can be instantiated
-impl<MODE, BOSR> Sampling<(MODE, BOSR, SrValid)> {
+impl<MODE, BOSR, DIV> Sampling<(MODE, BOSR, SrValid), DIV> {
+    ///Instantiate a builder loaded with the content of an existing command, for example to
+    ///modify one field of a previously sent configuration.
+    ///
+    ///Only the register content is used, the address is the one of this builder.
+    ///
+    /// # Safety
+    ///
+    /// `MODE`, `BOSR` and `DIV` markers must match the USB/NORMAL, BOSR and CLKIDIV2 bits of the
+    /// command.
+    pub const unsafe fn from_command(cmd: Command<()>) -> Self {
+        Self {
+            data: (registers::SAMPLING as u16) << 9 | cmd.value(),
+            t: PhantomData::<(MODE, BOSR, SrValid)>,
+            div: PhantomData::<DIV>,
+        }
+    }
+}
+
+impl<MODE, BOSR, SR, DIV> Sampling<(MODE, BOSR, SR), DIV> {
     /// Instanciate a command
-    pub fn into_command(self) -> Command<()> {
+    pub const fn into_command(self) -> Command<()>
+    where
+        SR: SrIsValid,
+    {
+        Command::<()> {
+            data: self.data,
+            t: PhantomData::<()>,
+        }
+    }
+    ///Instantiate a command, keeping the builder to derive other commands from it.
+    pub const fn to_command(&self) -> Command<()>
+    where
+        SR: SrIsValid,
+    {
         Command::<()> {
             data: self.data,
             t: PhantomData::<()>,
@@ -429,88 +809,94 @@ impl<MODE, BOSR> Sampling<(MODE, BOSR, SrValid)> {
 }
 
 //field accessible in raw mode
-impl<MODE, BOSR, SR> Sampling<(MODE, BOSR, SR)> {
-    pub fn usb_normal(self) -> UsbNormal<(MODE, BOSR, SR)> {
+impl<MODE, BOSR, SR, DIV> Sampling<(MODE, BOSR, SR), DIV> {
+    pub const fn usb_normal(self) -> UsbNormal<(MODE, BOSR, SR), DIV> {
         UsbNormal { cmd: self }
     }
-    pub fn bosr(self) -> Bosr<(MODE, BOSR, SR)> {
+    pub const fn bosr(self) -> Bosr<(MODE, BOSR, SR), DIV> {
         Bosr { cmd: self }
     }
-    pub fn sr(self) -> Sr<(MODE, BOSR, SR)> {
+    pub const fn sr(self) -> Sr<(MODE, BOSR, SR), DIV> {
         Sr { cmd: self }
     }
 }
 
 /// Field writer. Allow to select USB or Normal mode. Invalidate `Sr` field.
-pub struct UsbNormal<T> {
-    cmd: Sampling<T>,
+pub struct UsbNormal<T, DIV> {
+    cmd: Sampling<T, DIV>,
 }
 
-impl<MODE, BOSR, SR> UsbNormal<(MODE, BOSR, SR)> {
+impl<MODE, BOSR, SR, DIV> UsbNormal<(MODE, BOSR, SR), DIV> {
     #[must_use]
-    pub fn clear_bit(mut self) -> Sampling<(Normal, BOSR, SrInvalid)> {
+    pub const fn clear_bit(mut self) -> Sampling<(Normal, BOSR, SrInvalid), DIV> {
         self.cmd.data &= !(0b1 << 0);
-        Sampling::<(Normal, BOSR, SrInvalid)> {
+        Sampling::<(Normal, BOSR, SrInvalid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(Normal, BOSR, SrInvalid)>,
+            div: PhantomData::<DIV>,
         }
     }
     #[must_use]
-    pub fn set_bit(mut self) -> Sampling<(Usb, BOSR, SrInvalid)> {
+    pub const fn set_bit(mut self) -> Sampling<(Usb, BOSR, SrInvalid), DIV> {
         self.cmd.data |= 0b1 << 0;
-        Sampling::<(Usb, BOSR, SrInvalid)> {
+        Sampling::<(Usb, BOSR, SrInvalid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(Usb, BOSR, SrInvalid)>,
+            div: PhantomData::<DIV>,
         }
     }
     #[must_use]
-    pub fn normal(mut self) -> Sampling<(Normal, BOSR, SrInvalid)> {
+    pub const fn normal(mut self) -> Sampling<(Normal, BOSR, SrInvalid), DIV> {
         self.cmd.data &= !(0b1 << 0);
-        Sampling::<(Normal, BOSR, SrInvalid)> {
+        Sampling::<(Normal, BOSR, SrInvalid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(Normal, BOSR, SrInvalid)>,
+            div: PhantomData::<DIV>,
         }
     }
     #[must_use]
-    pub fn usb(mut self) -> Sampling<(Usb, BOSR, SrInvalid)> {
+    pub const fn usb(mut self) -> Sampling<(Usb, BOSR, SrInvalid), DIV> {
         self.cmd.data |= 0b1 << 0;
-        Sampling::<(Usb, BOSR, SrInvalid)> {
+        Sampling::<(Usb, BOSR, SrInvalid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(Usb, BOSR, SrInvalid)>,
+            div: PhantomData::<DIV>,
         }
     }
 }
 
 /// Field writer. Select the Base Over-Sampling Rate. Invalidate `Sr` field.
-pub struct Bosr<T> {
-    cmd: Sampling<T>,
+pub struct Bosr<T, DIV> {
+    cmd: Sampling<T, DIV>,
 }
 
-impl<MODE, BOSR, SR> Bosr<(MODE, BOSR, SR)> {
+impl<MODE, BOSR, SR, DIV> Bosr<(MODE, BOSR, SR), DIV> {
     #[must_use]
-    pub fn clear_bit(mut self) -> Sampling<(MODE, BosrClear, SrInvalid)> {
+    pub const fn clear_bit(mut self) -> Sampling<(MODE, BosrClear, SrInvalid), DIV> {
         self.cmd.data &= !(0b1 << 1);
-        Sampling::<(MODE, BosrClear, SrInvalid)> {
+        Sampling::<(MODE, BosrClear, SrInvalid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(MODE, BosrClear, SrInvalid)>,
+            div: PhantomData::<DIV>,
         }
     }
     #[must_use]
-    pub fn set_bit(mut self) -> Sampling<(MODE, BosrSet, SrInvalid)> {
+    pub const fn set_bit(mut self) -> Sampling<(MODE, BosrSet, SrInvalid), DIV> {
         self.cmd.data |= 0b1 << 1;
-        Sampling::<(MODE, BosrSet, SrInvalid)> {
+        Sampling::<(MODE, BosrSet, SrInvalid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(MODE, BosrSet, SrInvalid)>,
+            div: PhantomData::<DIV>,
         }
     }
 }
 
 /// Field writer. Allow to write raw bits into the sr field.
-pub struct Sr<T> {
-    cmd: Sampling<T>,
+pub struct Sr<T, DIV> {
+    cmd: Sampling<T, DIV>,
 }
 
-impl<MODE, BOSR, SR> Sr<(MODE, BOSR, SR)> {
+impl<MODE, BOSR, SR, DIV> Sr<(MODE, BOSR, SR), DIV> {
     //impl_bits!(unsafe, Sampling<T>, 4, 2);
     /// Set the field with raw bits.
     ///
@@ -518,119 +904,367 @@ impl<MODE, BOSR, SR> Sr<(MODE, BOSR, SR)> {
     ///
     /// This is unsafe because it assume valid bits combination that may actually not. Please read
     /// the datasheet to know what are the valid combinations.
-    pub unsafe fn bits(mut self, value: u8) -> Sampling<(MODE, BOSR, SrValid)> {
+    pub const unsafe fn bits(mut self, value: u8) -> Sampling<(MODE, BOSR, SrValid), DIV> {
         let mask = !((!0) << 4) << 2;
         self.cmd.data = self.cmd.data & !mask | (value as u16) << 2 & mask;
-        Sampling::<(MODE, BOSR, SrValid)> {
+        Sampling::<(MODE, BOSR, SrValid), DIV> {
             data: self.cmd.data,
             t: PhantomData::<(MODE, BOSR, SrValid)>,
+            div: PhantomData::<DIV>,
+        }
+    }
+}
+
+impl<MODE, BOSR, SR, DIV> Sr<(MODE, BOSR, SR), DIV>
+where
+    (MODE, BOSR): SrTable,
+{
+    /// Set the field with raw bits, fails if the value isn't valid for the USB/NORMAL and BOSR
+    /// settings of the builder.
+    pub const fn try_bits(
+        self,
+        value: u8,
+    ) -> Result<Sampling<(MODE, BOSR, SrValid), DIV>, InvalidSr> {
+        if value < 16 && <(MODE, BOSR)>::VALID_SR & 1 << value != 0 {
+            Ok(unsafe { self.bits(value) })
+        } else {
+            Err(InvalidSr)
         }
     }
 }
 
-impl<BOSR, SR> Sr<(Normal, BOSR, SR)> {
+impl<BOSR, SR, DIV> Sr<(Normal, BOSR, SR), DIV> {
     #[must_use]
-    pub fn sr_0b0000(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b0000(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b0000) }
     }
     #[must_use]
-    pub fn sr_0b0001(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b0001(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b0001) }
     }
     #[must_use]
-    pub fn sr_0b0010(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b0010(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b0010) }
     }
     #[must_use]
-    pub fn sr_0b0011(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b0011(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b0011) }
     }
     #[must_use]
-    pub fn sr_0b0110(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b0110(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b0110) }
     }
     #[must_use]
-    pub fn sr_0b0111(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b0111(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b0111) }
     }
     #[must_use]
-    pub fn sr_0b1000(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b1000(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b1000) }
     }
     #[must_use]
-    pub fn sr_0b1001(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b1001(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b1001) }
     }
     #[must_use]
-    pub fn sr_0b1010(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b1010(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b1010) }
     }
     #[must_use]
-    pub fn sr_0b1011(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b1011(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b1011) }
     }
     #[must_use]
-    pub fn sr_0b1111(self) -> Sampling<(Normal, BOSR, SrValid)> {
+    pub const fn sr_0b1111(self) -> Sampling<(Normal, BOSR, SrValid), DIV> {
         unsafe { self.bits(0b1111) }
     }
 }
 
-impl<SR> Sr<(Usb, BosrClear, SR)> {
+impl<SR, DIV> Sr<(Usb, BosrClear, SR), DIV> {
     #[must_use]
-    pub fn sr_0b0000(self) -> Sampling<(Usb, BosrClear, SrValid)> {
+    pub const fn sr_0b0000(self) -> Sampling<(Usb, BosrClear, SrValid), DIV> {
         unsafe { self.bits(0b0000) }
     }
     #[must_use]
-    pub fn sr_0b0001(self) -> Sampling<(Usb, BosrClear, SrValid)> {
+    pub const fn sr_0b0001(self) -> Sampling<(Usb, BosrClear, SrValid), DIV> {
         unsafe { self.bits(0b0001) }
     }
     #[must_use]
-    pub fn sr_0b0010(self) -> Sampling<(Usb, BosrClear, SrValid)> {
+    pub const fn sr_0b0010(self) -> Sampling<(Usb, BosrClear, SrValid), DIV> {
         unsafe { self.bits(0b0010) }
     }
     #[must_use]
-    pub fn sr_0b0011(self) -> Sampling<(Usb, BosrClear, SrValid)> {
+    pub const fn sr_0b0011(self) -> Sampling<(Usb, BosrClear, SrValid), DIV> {
         unsafe { self.bits(0b0011) }
     }
     #[must_use]
-    pub fn sr_0b0110(self) -> Sampling<(Usb, BosrClear, SrValid)> {
+    pub const fn sr_0b0110(self) -> Sampling<(Usb, BosrClear, SrValid), DIV> {
         unsafe { self.bits(0b0110) }
     }
     #[must_use]
-    pub fn sr_0b0111(self) -> Sampling<(Usb, BosrClear, SrValid)> {
+    pub const fn sr_0b0111(self) -> Sampling<(Usb, BosrClear, SrValid), DIV> {
         unsafe { self.bits(0b0111) }
     }
 }
 
-impl<SR> Sr<(Usb, BosrSet, SR)> {
+impl<SR, DIV> Sr<(Usb, BosrSet, SR), DIV> {
     #[must_use]
-    pub fn sr_0b1000(self) -> Sampling<(Usb, BosrSet, SrValid)> {
+    pub const fn sr_0b1000(self) -> Sampling<(Usb, BosrSet, SrValid), DIV> {
         unsafe { self.bits(0b1000) }
     }
     #[must_use]
-    pub fn sr_0b1001(self) -> Sampling<(Usb, BosrSet, SrValid)> {
+    pub const fn sr_0b1001(self) -> Sampling<(Usb, BosrSet, SrValid), DIV> {
         unsafe { self.bits(0b1001) }
     }
     #[must_use]
-    pub fn sr_0b1010(self) -> Sampling<(Usb, BosrSet, SrValid)> {
+    pub const fn sr_0b1010(self) -> Sampling<(Usb, BosrSet, SrValid), DIV> {
         unsafe { self.bits(0b1010) }
     }
     #[must_use]
-    pub fn sr_0b1011(self) -> Sampling<(Usb, BosrSet, SrValid)> {
+    pub const fn sr_0b1011(self) -> Sampling<(Usb, BosrSet, SrValid), DIV> {
         unsafe { self.bits(0b1011) }
     }
     #[must_use]
-    pub fn sr_0b1111(self) -> Sampling<(Usb, BosrSet, SrValid)> {
+    pub const fn sr_0b1111(self) -> Sampling<(Usb, BosrSet, SrValid), DIV> {
         unsafe { self.bits(0b1111) }
     }
 }
 
-impl_toggle_writer!(Clkidiv2<T>, Sampling<T>, 6);
-impl_toggle_writer!(Clkodiv2<T>, Sampling<T>, 7);
+/// Field writer. Divide the master clock by 2 before the core. Change the divider marker.
+pub struct Clkidiv2<T, DIV> {
+    cmd: Sampling<T, DIV>,
+}
+
+impl<T, DIV> Clkidiv2<T, DIV> {
+    const fn write<D>(self, value: bool) -> Sampling<T, D> {
+        Sampling::<T, D> {
+            data: self.cmd.data & !(1 << 6) | (value as u16) << 6,
+            t: PhantomData::<T>,
+            div: PhantomData::<D>,
+        }
+    }
+    ///Writes raw bit to the field. The divider is then only known at runtime.
+    #[must_use]
+    pub const fn bit(self, value: bool) -> Sampling<T, AnyDiv> {
+        self.write(value)
+    }
+    /// Sets the bit field.
+    #[must_use]
+    pub const fn set_bit(self) -> Sampling<T, Div2> {
+        self.write(true)
+    }
+    /// Clears the bit field.
+    #[must_use]
+    pub const fn clear_bit(self) -> Sampling<T, Div1> {
+        self.write(false)
+    }
+    #[must_use]
+    pub const fn enable(self) -> Sampling<T, Div2> {
+        self.write(true)
+    }
+    #[must_use]
+    pub const fn disable(self) -> Sampling<T, Div1> {
+        self.write(false)
+    }
+}
+
+///Field writer.
+pub struct Clkodiv2<T, DIV> {
+    cmd: Sampling<T, DIV>,
+}
+
+impl<T, DIV> Clkodiv2<T, DIV> {
+    impl_bitsetters!(Sampling<T, DIV>, 7);
+}
+
+/// Sampling configuration only known at runtime, validated when building the command.
+///
+/// Unlike [`Sampling`], fields are set in place and the type doesn't change, which suits rates
+/// coming from a runtime setting. Fields start at their reset values.
+///
+/// ## Example
+/// ```
+/// # use wm8731_alt::command::sampling::SamplingDyn;
+/// let mut sampling = SamplingDyn::new();
+/// sampling.set_mode(true);
+/// sampling.set_sr(0b1000);
+/// assert!(sampling.try_into_command().is_err());
+/// sampling.set_bosr(true);
+/// assert!(sampling.try_into_command().is_ok());
+/// ```
+#[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SamplingDyn {
+    usb: bool,
+    bosr: bool,
+    sr: u8,
+    clkidiv2: bool,
+    clkodiv2: bool,
+}
+
+impl SamplingDyn {
+    pub const fn new() -> Self {
+        Self {
+            usb: false,
+            bosr: false,
+            sr: 0,
+            clkidiv2: false,
+            clkodiv2: false,
+        }
+    }
+    ///Select USB mode when `true`, normal mode otherwise.
+    pub fn set_mode(&mut self, usb: bool) {
+        self.usb = usb;
+    }
+    pub fn set_bosr(&mut self, bosr: bool) {
+        self.bosr = bosr;
+    }
+    ///Set the SR field, it's checked against USB/NORMAL and BOSR when building the command.
+    pub fn set_sr(&mut self, sr: u8) {
+        self.sr = sr;
+    }
+    pub fn set_clkidiv2(&mut self, clkidiv2: bool) {
+        self.clkidiv2 = clkidiv2;
+    }
+    pub fn set_clkodiv2(&mut self, clkodiv2: bool) {
+        self.clkodiv2 = clkodiv2;
+    }
+    ///Build the command, fails if SR isn't valid for the USB/NORMAL and BOSR settings.
+    pub const fn try_into_command(&self) -> Result<Command<()>, InvalidSr> {
+        let valid = match (self.usb, self.bosr) {
+            (false, _) => <(Normal, BosrClear)>::VALID_SR,
+            (true, false) => <(Usb, BosrClear)>::VALID_SR,
+            (true, true) => <(Usb, BosrSet)>::VALID_SR,
+        };
+        if self.sr < 16 && valid & 1 << self.sr != 0 {
+            let data = (registers::SAMPLING as u16) << 9
+                | (self.clkodiv2 as u16) << 7
+                | (self.clkidiv2 as u16) << 6
+                | (self.sr as u16) << 2
+                | (self.bosr as u16) << 1
+                | self.usb as u16;
+            Ok(Command::<()> {
+                data,
+                t: PhantomData::<()>,
+            })
+        } else {
+            Err(InvalidSr)
+        }
+    }
+}
 
 #[cfg(test)]
+#[allow(clippy::non_minimal_cfg)]
 mod tests {
     use super::*;
+    use std::vec::Vec;
+
+    //Typed presets of each master clock with their nominal rates, in `SUPPORTED` order.
+    fn presets_12m288() -> Vec<(u32, u32, Command<()>)> {
+        let rate = || sampling_with_mclk(Mclk12M288).sample_rate();
+        std::vec![
+            (48_000, 48_000, rate().adc48k_dac48k().into_command()),
+            (48_000, 8_000, rate().adc48k_dac8k().into_command()),
+            (8_000, 48_000, rate().adc8k_dac48k().into_command()),
+            (8_000, 8_000, rate().adc8k_dac8k().into_command()),
+            (32_000, 32_000, rate().adc32k_dac32k().into_command()),
+            (96_000, 96_000, rate().adc96k_dac96k().into_command()),
+        ]
+    }
+    fn presets_18m432() -> Vec<(u32, u32, Command<()>)> {
+        let rate = || sampling_with_mclk(Mclk18M432).sample_rate();
+        std::vec![
+            (48_000, 48_000, rate().adc48k_dac48k().into_command()),
+            (48_000, 8_000, rate().adc48k_dac8k().into_command()),
+            (8_000, 48_000, rate().adc8k_dac48k().into_command()),
+            (8_000, 8_000, rate().adc8k_dac8k().into_command()),
+            (32_000, 32_000, rate().adc32k_dac32k().into_command()),
+            (96_000, 96_000, rate().adc96k_dac96k().into_command()),
+        ]
+    }
+    fn presets_11m2896() -> Vec<(u32, u32, Command<()>)> {
+        let rate = || sampling_with_mclk(Mclk11M2896).sample_rate();
+        std::vec![
+            (44_100, 44_100, rate().adc44k1_dac44k1().into_command()),
+            (44_100, 8_000, rate().adc44k1_dac8k().into_command()),
+            (8_000, 44_100, rate().adc8k_dac44k1().into_command()),
+            (8_000, 8_000, rate().adc8k_dac8k().into_command()),
+            (88_200, 88_200, rate().adc88k2_dac88k2().into_command()),
+        ]
+    }
+    fn presets_16m9344() -> Vec<(u32, u32, Command<()>)> {
+        let rate = || sampling_with_mclk(Mclk16M9344).sample_rate();
+        std::vec![
+            (44_100, 44_100, rate().adc44k1_dac44k1().into_command()),
+            (44_100, 8_000, rate().adc44k1_dac8k().into_command()),
+            (8_000, 44_100, rate().adc8k_dac44k1().into_command()),
+            (8_000, 8_000, rate().adc8k_dac8k().into_command()),
+            (88_200, 88_200, rate().adc88k2_dac88k2().into_command()),
+        ]
+    }
+    fn presets_12m() -> Vec<(u32, u32, Command<()>)> {
+        let rate = || sampling_with_mclk(Mclk12M).sample_rate();
+        std::vec![
+            (48_000, 48_000, rate().adc48k_dac48k().into_command()),
+            (44_100, 44_100, rate().adc44k1_dac44k1().into_command()),
+            (48_000, 8_000, rate().adc48k_dac8k().into_command()),
+            (44_100, 8_000, rate().adc44k1_dac8k().into_command()),
+            (8_000, 48_000, rate().adc8k_dac48k().into_command()),
+            (8_000, 44_100, rate().adc8k_dac44k1().into_command()),
+            (8_000, 8_000, rate().adc8k_dac8k().into_command()),
+            (8_000, 8_000, rate().adc8k_dac8k_bis().into_command()),
+            (32_000, 32_000, rate().adc32k_dac32k().into_command()),
+            (96_000, 96_000, rate().adc96k_dac96k().into_command()),
+            (88_200, 88_200, rate().adc88k2_dac88k2().into_command()),
+        ]
+    }
+    #[test]
+    fn presets_match_supported() {
+        fn check<MCLK: Mclk>(presets: Vec<(u32, u32, Command<()>)>) {
+            let test: Vec<_> = presets
+                .iter()
+                .map(|&(adc, dac, cmd)| (adc, dac, cmd.value() as u8))
+                .collect();
+            assert!(
+                test == MCLK::SUPPORTED,
+                "Got {:?},expected {:?}",
+                test,
+                MCLK::SUPPORTED
+            );
+        }
+        check::<Mclk12M288>(presets_12m288());
+        check::<Mclk18M432>(presets_18m432());
+        check::<Mclk11M2896>(presets_11m2896());
+        check::<Mclk16M9344>(presets_16m9344());
+        check::<Mclk12M>(presets_12m());
+    }
+    #[test]
+    fn mclk_input_hz() {
+        let cmd = sampling_with_mclk(Mclk12M288).sample_rate().adc96k_dac96k();
+        let test = cmd.mclk_input_hz(24_576_000).map(|c| c.into_command().data);
+        let expected = Ok(0b1000 << 9 | 0b0101_1100);
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let test = cmd.mclk_input_hz(12_288_000).map(|c| c.into_command().data);
+        let expected = Ok(0b1000 << 9 | 0b0001_1100);
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let test = cmd.mclk_input_hz(18_432_000).map(|c| c.into_command().data);
+        let expected = Err(UnsupportedClock);
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let test = cmd.mclk_input_hz(24_576_000).map(|c| c.clkidiv_factor());
+        assert!(
+            test == Ok(2),
+            "Got {:?},expected {:?}",
+            test,
+            Ok::<_, ()>(2)
+        );
+        match cmd.mclk_input_hz(12_288_000) {
+            Ok(SamplingClkDiv::Div1(s)) => {
+                let test = s.clkidiv_factor();
+                assert!(test == 1, "Got {},expected {}", test, 1);
+            }
+            other => panic!("Got {:?},expected Div1", other),
+        }
+    }
     // all() to compile, any() to not compile
     #[cfg(all())]
     fn _should_compile() {
@@ -644,6 +1278,168 @@ mod tests {
         //setting sr from default is valid
         new_cmd.sr().sr_0b0000().into_command();
     }
+    #[test]
+    fn sample_rate_bits() {
+        let cmd = sampling_with_mclk(Mclk12M288)
+            .sample_rate()
+            .adc96k_dac96k()
+            .into_command();
+        let expected = 0b1000 << 9 | 0b0001_1100;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+    }
+    #[test]
+    fn try_adc_dac() {
+        let cmd = sampling_with_mclk(Mclk12M)
+            .sample_rate()
+            .try_adc_dac(8_000, 8_000)
+            .map(|s| s.into_command());
+        let expected = Ok(sampling_with_mclk(Mclk12M)
+            .sample_rate()
+            .adc8k_dac8k()
+            .into_command());
+        assert!(cmd == expected, "Got {:?},expected {:?}", cmd, expected);
+        let cmd = sampling_with_mclk(Mclk11M2896)
+            .sample_rate()
+            .try_adc_dac(44_100, 8_000)
+            .map(|s| s.into_command());
+        let expected = Ok(sampling_with_mclk(Mclk11M2896)
+            .sample_rate()
+            .adc44k1_dac8k()
+            .into_command());
+        assert!(cmd == expected, "Got {:?},expected {:?}", cmd, expected);
+        let cmd = sampling_with_mclk(Mclk12M288)
+            .sample_rate()
+            .try_adc_dac(44_100, 44_100)
+            .map(|s| s.into_command());
+        let expected = Err(UnsupportedRate);
+        assert!(cmd == expected, "Got {:?},expected {:?}", cmd, expected);
+    }
+    #[test]
+    fn sample_rate_hz() {
+        let test = SampleRateHz::ADC44K1_DAC44K1.bits::<Mclk12M>();
+        let expected = Ok((true, true, 0b1000));
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let test = SampleRateHz::ADC44K1_DAC44K1.bits::<Mclk12M288>();
+        let expected = Err(UnsupportedRate);
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        let cmd = sampling_with_mclk(Mclk18M432)
+            .sample_rate()
+            .hz(SampleRateHz::ADC96K_DAC96K)
+            .map(|s| s.into_command());
+        let expected = Ok(sampling_with_mclk(Mclk18M432)
+            .sample_rate()
+            .adc96k_dac96k()
+            .into_command());
+        assert!(cmd == expected, "Got {:?},expected {:?}", cmd, expected);
+    }
+    #[test]
+    fn supported_rates() {
+        //every listed rate is reachable and selects the first preset with these rates
+        fn check<MCLK: Mclk>(
+            mclk: fn() -> Sampling<(MCLK, SrInvalid)>,
+            presets: Vec<(u32, u32, Command<()>)>,
+        ) {
+            let rates: Vec<_> = MCLK::supported_rates()
+                .iter()
+                .map(|&(adc, dac, _)| (adc, dac))
+                .collect();
+            let expected: Vec<_> = presets.iter().map(|&(adc, dac, _)| (adc, dac)).collect();
+            assert!(rates == expected, "Got {:?},expected {:?}", rates, expected);
+            for &(adc, dac) in rates.iter() {
+                let test = mclk()
+                    .sample_rate()
+                    .try_adc_dac(adc, dac)
+                    .map(|s| s.into_command());
+                let expected = presets
+                    .iter()
+                    .find(|&&(a, d, _)| a == adc && d == dac)
+                    .map(|&(_, _, cmd)| cmd)
+                    .ok_or(UnsupportedRate);
+                assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+            }
+        }
+        check(|| sampling_with_mclk(Mclk12M288), presets_12m288());
+        check(|| sampling_with_mclk(Mclk18M432), presets_18m432());
+        check(|| sampling_with_mclk(Mclk11M2896), presets_11m2896());
+        check(|| sampling_with_mclk(Mclk16M9344), presets_16m9344());
+        check(|| sampling_with_mclk(Mclk12M), presets_12m());
+    }
+    #[test]
+    fn sampling_dyn() {
+        let mut dyn_sampling = SamplingDyn::new();
+        let test = dyn_sampling.try_into_command();
+        let expected = Ok(sampling().into_command());
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        dyn_sampling.set_mode(true);
+        dyn_sampling.set_bosr(true);
+        dyn_sampling.set_sr(0b1111);
+        dyn_sampling.set_clkidiv2(true);
+        let test = dyn_sampling.try_into_command();
+        let expected = Ok(sampling()
+            .usb_normal()
+            .usb()
+            .bosr()
+            .set_bit()
+            .sr()
+            .sr_0b1111()
+            .clkidiv2()
+            .enable()
+            .into_command());
+        assert!(test == expected, "Got {:?},expected {:?}", test, expected);
+        dyn_sampling.set_bosr(false);
+        let test = dyn_sampling.try_into_command();
+        assert!(
+            test == Err(InvalidSr),
+            "Got {:?},expected {:?}",
+            test,
+            InvalidSr
+        );
+    }
+    #[test]
+    fn sr_try_bits() {
+        let cmd = sampling().sr().try_bits(0b1111).map(|s| s.into_command());
+        let expected = Ok(sampling().sr().sr_0b1111().into_command());
+        assert!(cmd == expected, "Got {:?},expected {:?}", cmd, expected);
+        let cmd = sampling().sr().try_bits(0b0100).map(|s| s.into_command());
+        assert!(
+            cmd == Err(InvalidSr),
+            "Got {:?},expected {:?}",
+            cmd,
+            InvalidSr
+        );
+        let sampling = sampling().usb_normal().usb();
+        let cmd = sampling.sr().try_bits(0b1000).map(|s| s.into_command());
+        assert!(
+            cmd == Err(InvalidSr),
+            "Got {:?},expected {:?}",
+            cmd,
+            InvalidSr
+        );
+        let cmd = sampling.bosr().set_bit().sr().try_bits(0b1000);
+        let cmd = cmd.map(|s| s.into_command());
+        let expected = Ok(sampling.bosr().set_bit().sr().sr_0b1000().into_command());
+        assert!(cmd == expected, "Got {:?},expected {:?}", cmd, expected);
+        let cmd = sampling.sr().try_bits(0xFF).map(|s| s.into_command());
+        assert!(
+            cmd == Err(InvalidSr),
+            "Got {:?},expected {:?}",
+            cmd,
+            InvalidSr
+        );
+    }
+    #[test]
+    fn mclk_freq() {
+        fn freq<M: Mclk>(_: M) -> u32 {
+            M::FREQ_HZ
+        }
+        let test = freq(Mclk11M2896);
+        assert!(test == 11_289_600, "Got {}, expected 11289600", test);
+    }
     // all() to compile, any() to not compile
     #[cfg(any())]
     fn _should_compile_warn() {
@@ -690,4 +1486,20 @@ mod tests {
         //error, cannot set this sr value with this bosr value
         let _ = new_cmd.usb_normal().usb().bosr().set_bit().sr().sr_0b0000();
     }
+    #[test]
+    fn clkidiv_factor() {
+        let cmd = sampling_with_mclk(Mclk12M288).sample_rate().adc48k_dac48k();
+        assert!(cmd.clkidiv_factor() == 1);
+        let cmd = cmd.clkidiv2().enable();
+        assert!(cmd.clkidiv_factor() == 2);
+        let expected = 0b1000 << 9 | 0b0100_0000;
+        assert!(
+            cmd.data == expected,
+            "Got {:#b},expected {:#b}",
+            cmd.data,
+            expected
+        );
+        let cmd = cmd.clkidiv2().disable();
+        assert!(cmd.clkidiv_factor() == 1);
+    }
 }
@@ -0,0 +1,215 @@
+//! Serde support, commands and builders are serialized as their raw 16 bits control word.
+//!
+//! Builders carrying a type-state are handled apart, since markers can't be recovered from the
+//! control word:
+//! - [`DigitalAudioInterface`] is serialized whatever its markers, and deserialized with
+//!   runtime format and mode, as [`DigitalAudioInterface::from_command`] does.
+//! - [`Sampling`] is only serialized, once its sample rate is set. It isn't deserialized,
+//!   building it from a control word is `unsafe`, see [`Sampling::from_command`]. Deserialize a
+//!   [`Command`] instead.
+
+use super::active_control::ActiveControl;
+use super::analogue_audio_path::AnalogueAudioPath;
+use super::digital_audio_interface::state_marker::{AnyFormat, AnyMode};
+use super::digital_audio_interface::DigitalAudioInterface;
+use super::digital_audio_path::DigitalAudioPath;
+use super::headphone_out::{LeftHeadphoneOut, RightHeadphoneOut};
+use super::line_in::{LeftLineIn, RightLineIn};
+use super::power_down::PowerDown;
+use super::sampling::state_marker::SrIsValid;
+use super::sampling::Sampling;
+use super::Command;
+use crate::registers;
+use core::convert::TryFrom;
+use core::fmt;
+use serde::de::{Deserialize, Deserializer, Error, Expected, Unexpected};
+use serde::ser::{self, Serialize, Serializer};
+
+impl Serialize for Command<()> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.data)
+    }
+}
+
+///Deserialization fails if the address doesn't correspond to a register.
+impl<'de> Deserialize<'de> for Command<()> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = u16::deserialize(deserializer)?;
+        Command::try_from(data).map_err(|_| {
+            D::Error::invalid_value(
+                Unexpected::Unsigned(data as u64),
+                &"a control word with a valid register address",
+            )
+        })
+    }
+}
+
+///Expected value for a builder, a control word with the given register address.
+struct ExpectedAddress(u8);
+
+impl Expected for ExpectedAddress {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a control word with address {}", self.0)
+    }
+}
+
+macro_rules! impl_serde_builder {
+    ($($builder:ty => $address:path),*) => {
+        $(
+            impl Serialize for $builder {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.into_command().serialize(serializer)
+                }
+            }
+
+            ///Deserialization fails if the address isn't the one of this builder.
+            impl<'de> Deserialize<'de> for $builder {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let cmd = Command::<()>::deserialize(deserializer)?;
+                    if cmd.address() == $address {
+                        Ok(<$builder>::from_command(cmd))
+                    } else {
+                        Err(D::Error::invalid_value(
+                            Unexpected::Unsigned(cmd.data as u64),
+                            &ExpectedAddress($address),
+                        ))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_serde_builder!(
+    LeftLineIn => registers::LEFT_LINE_IN,
+    RightLineIn => registers::RIGHT_LINE_IN,
+    LeftHeadphoneOut => registers::LEFT_HEADPHONE_OUT,
+    RightHeadphoneOut => registers::RIGHT_HEADPHONE_OUT,
+    AnalogueAudioPath => registers::ANALOGUE_AUDIO_PATH,
+    DigitalAudioPath => registers::DIGITAL_AUDIO_PATH,
+    PowerDown => registers::POWER_DOWN,
+    ActiveControl => registers::ACTIVE_CONTROL
+);
+
+///Serialization fails on a combination the codec doesn't support, see
+///[`DigitalAudioInterface::try_into_command`].
+impl<T> Serialize for DigitalAudioInterface<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let cmd = self
+            .try_into_command()
+            .map_err(|_| <S::Error as ser::Error>::custom("incoherent format and word length"))?;
+        cmd.serialize(serializer)
+    }
+}
+
+///Deserialization fails if the address isn't the digital audio interface one, or on a
+///combination the codec doesn't support.
+impl<'de> Deserialize<'de> for DigitalAudioInterface<(AnyFormat, AnyMode)> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cmd = Command::<()>::deserialize(deserializer)?;
+        let builder = DigitalAudioInterface::from_command(cmd);
+        if cmd.address() != registers::DIGITAL_AUDIO_INTERFACE {
+            Err(D::Error::invalid_value(
+                Unexpected::Unsigned(cmd.data as u64),
+                &ExpectedAddress(registers::DIGITAL_AUDIO_INTERFACE),
+            ))
+        } else if builder.try_into_command().is_err() {
+            Err(D::Error::invalid_value(
+                Unexpected::Unsigned(cmd.data as u64),
+                &"a supported format and word length combination",
+            ))
+        } else {
+            Ok(builder)
+        }
+    }
+}
+
+impl<MCLK, SR: SrIsValid, DIV> Serialize for Sampling<(MCLK, SR), DIV> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_command().serialize(serializer)
+    }
+}
+
+impl<MODE, BOSR, SR: SrIsValid, DIV> Serialize for Sampling<(MODE, BOSR, SR), DIV> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_command().serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    #[test]
+    fn round_trip() {
+        let cmd = power_down().poweroff().disable().into_command();
+        let json = serde_json::to_string(&cmd).unwrap();
+        let expected = "3103";
+        assert!(json == expected, "Got {}, expected {}", json, expected);
+        let test: Command<()> = serde_json::from_str(&json).unwrap();
+        assert!(test == cmd, "Got {:?}, expected {:?}", test, cmd);
+        let test: PowerDown = serde_json::from_str(&json).unwrap();
+        let test = test.into_command();
+        assert!(test == cmd, "Got {:?}, expected {:?}", test, cmd);
+    }
+    #[test]
+    fn invalid_address() {
+        let test = serde_json::from_str::<Command<()>>("5120");
+        assert!(test.is_err(), "Got {:?}, expected an error", test);
+        let test = serde_json::from_str::<ActiveControl>("3103");
+        assert!(test.is_err(), "Got {:?}, expected an error", test);
+    }
+    #[test]
+    fn invalid_address_message() {
+        let test = serde_json::from_str::<ActiveControl>("3103").unwrap_err();
+        let test = std::string::ToString::to_string(&test);
+        assert!(
+            test.contains("a control word with address 9"),
+            "Got {:?}, expected the register address",
+            test
+        );
+    }
+    #[test]
+    fn digital_audio_interface_round_trip() {
+        use crate::command::digital_audio_interface::state_marker::{AnyFormat, AnyMode};
+        let cmd = digital_audio_interface()
+            .format()
+            .left_justified()
+            .iwl()
+            .iwl_32_bits();
+        let json = serde_json::to_string(&cmd).unwrap();
+        let expected = "3597";
+        assert!(json == expected, "Got {}, expected {}", json, expected);
+        let test: DigitalAudioInterface<(AnyFormat, AnyMode)> =
+            serde_json::from_str(&json).unwrap();
+        let test = test.into_command();
+        let expected = cmd.into_command();
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        //right justified with 32 bits word length
+        let test = serde_json::from_str::<DigitalAudioInterface<(AnyFormat, AnyMode)>>("3596");
+        assert!(test.is_err(), "Got {:?}, expected an error", test);
+        let test = DigitalAudioInterface::from_command(Command::try_from(3596).unwrap());
+        let test = serde_json::to_string(&test);
+        assert!(test.is_err(), "Got {:?}, expected an error", test);
+        let test = serde_json::from_str::<DigitalAudioInterface<(AnyFormat, AnyMode)>>("3103");
+        assert!(test.is_err(), "Got {:?}, expected an error", test);
+    }
+    #[test]
+    fn sampling_serialize() {
+        use crate::command::sampling::Mclk12M288;
+        let cmd = sampling_with_mclk(Mclk12M288).sample_rate().adc48k_dac48k();
+        let json = serde_json::to_string(&cmd).unwrap();
+        let expected = serde_json::to_string(&cmd.into_command()).unwrap();
+        assert!(json == expected, "Got {}, expected {}", json, expected);
+        let cmd = sampling()
+            .usb_normal()
+            .usb()
+            .bosr()
+            .set_bit()
+            .sr()
+            .sr_0b1000();
+        let json = serde_json::to_string(&cmd).unwrap();
+        let expected = "4131";
+        assert!(json == expected, "Got {}, expected {}", json, expected);
+    }
+}
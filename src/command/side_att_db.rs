@@ -54,9 +54,9 @@ impl SideAttdB {
 
         const MIN: u8 = SideAttdB::MIN.inner;
         const MAX: u8 = SideAttdB::MAX.inner;
-        let r1 = high_limit - low_limit;
-        let r2 = MAX - MIN;
-        let res = (((input - low_limit) * r2 as i16) + r1 / 2) / (r1) + MIN as i16;
+        let r1 = high_limit as i32 - low_limit as i32;
+        let r2 = (MAX - MIN) as i32;
+        let res = ((input as i32 - low_limit as i32) * r2 + r1 / 2) / r1 + MIN as i32;
         let res = unsafe { SideAttdB::from_raw_unchecked(res as _) };
         Ok(res)
     }
@@ -90,13 +90,14 @@ impl SideAttdB {
 impl fmt::Display for SideAttdB {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let fmt = match self.inner {
-            0b00 => "-6",
-            0b01 => "-9",
-            0b10 => "-12",
-            0b11 => "-15",
-            _ => unreachable!(),
+            0b00 => "-6.0",
+            0b01 => "-9.0",
+            0b10 => "-12.0",
+            0b11 => "-15.0",
+            //only reachable through an invalid `from_raw_unchecked`
+            _ => return write!(f, "{:#b}", self.inner),
         };
-        write!(f, "{}dB", fmt)
+        write!(f, "{} dB", fmt)
     }
 }
 
@@ -104,6 +105,14 @@ impl fmt::Display for SideAttdB {
 mod tests {
     use super::*;
     #[test]
+    fn raw_round_trip() {
+        for raw in SideAttdB::MIN.inner..=SideAttdB::MAX.inner {
+            let db = unsafe { SideAttdB::from_raw_unchecked(raw) };
+            let test = SideAttdB::from_raw(db.into_raw());
+            assert!(test == db, "Got {:?}, expected {:?}", test, db);
+        }
+    }
+    #[test]
     fn scale_test() {
         let db = SideAttdB::from_scaled(0, 255, 0).unwrap().inner;
         let expected = SideAttdB::MIN.inner;
@@ -125,6 +134,19 @@ mod tests {
         assert!(db == expected, "Got {:?},expected {:?}", db, expected);
     }
     #[test]
+    fn scale_full_range_test() {
+        let db = SideAttdB::from_scaled(i16::MIN, i16::MAX, i16::MIN)
+            .unwrap()
+            .inner;
+        let expected = SideAttdB::MIN.inner;
+        assert!(db == expected, "Got {:#b},expected {:#b}", db, expected);
+        let db = SideAttdB::from_scaled(i16::MAX, i16::MIN, i16::MIN)
+            .unwrap()
+            .inner;
+        let expected = SideAttdB::MAX.inner;
+        assert!(db == expected, "Got {:#b},expected {:#b}", db, expected);
+    }
+    #[test]
     fn increase_decrease_saturation_test() {
         let mut test = SideAttdB::MAX;
         test.increase();
@@ -143,4 +165,11 @@ mod tests {
             SideAttdB::MIN
         );
     }
+    #[test]
+    fn display_test() {
+        use std::format;
+        let test = format!("{}", SideAttdB::N9DB);
+        let expected = "-9.0 dB";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+    }
 }
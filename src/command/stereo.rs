@@ -0,0 +1,221 @@
+//! Configure both channels of line inputs or headphone outputs at once.
+//!
+//! A [`StereoPair`] holds the left and right builders and apply the same field writes to both,
+//! producing one command per channel. Per-channel builders remain available for asymmetric
+//! configurations.
+//!
+//! ## Example
+//! ```
+//! # use wm8731_alt::command::stereo::StereoPair;
+//! let [left, right] = StereoPair::line_in()
+//!     .invol()
+//!     .bits(0x17)
+//!     .inmute()
+//!     .disable()
+//!     .into_commands();
+//! ```
+
+use super::headphone_out::{self, HeadphoneOut, HpVoldB, Hpvol, Zcen};
+use super::line_in::{self, InVoldB, Inmute, Invol, LineIn};
+use super::{left_headphone_out, left_line_in, right_headphone_out, right_line_in, Command};
+
+///Left and right builders or field writers, written together.
+pub struct StereoPair<L, R> {
+    left: L,
+    right: R,
+}
+
+type LineInPair = StereoPair<LineIn<line_in::Left>, LineIn<line_in::Right>>;
+type HeadphoneOutPair =
+    StereoPair<HeadphoneOut<headphone_out::Left>, HeadphoneOut<headphone_out::Right>>;
+
+macro_rules! impl_pair_toggle_writer {
+    ($writer:ident, $builder:ident, $left:ty, $right:ty) => {
+        impl StereoPair<$writer<$left>, $writer<$right>> {
+            ///Writes raw bit to the field of both channels.
+            #[must_use]
+            pub const fn bit(self, value: bool) -> StereoPair<$builder<$left>, $builder<$right>> {
+                StereoPair {
+                    left: self.left.bit(value),
+                    right: self.right.bit(value),
+                }
+            }
+            #[must_use]
+            pub const fn set_bit(self) -> StereoPair<$builder<$left>, $builder<$right>> {
+                self.bit(true)
+            }
+            #[must_use]
+            pub const fn clear_bit(self) -> StereoPair<$builder<$left>, $builder<$right>> {
+                self.bit(false)
+            }
+            #[must_use]
+            pub const fn enable(self) -> StereoPair<$builder<$left>, $builder<$right>> {
+                self.bit(true)
+            }
+            #[must_use]
+            pub const fn disable(self) -> StereoPair<$builder<$left>, $builder<$right>> {
+                self.bit(false)
+            }
+        }
+    };
+}
+
+impl LineInPair {
+    ///Instantiate builders for both line inputs.
+    pub const fn line_in() -> Self {
+        StereoPair {
+            left: left_line_in(),
+            right: right_line_in(),
+        }
+    }
+    pub const fn invol(self) -> StereoPair<Invol<line_in::Left>, Invol<line_in::Right>> {
+        StereoPair {
+            left: self.left.invol(),
+            right: self.right.invol(),
+        }
+    }
+    pub const fn inmute(self) -> StereoPair<Inmute<line_in::Left>, Inmute<line_in::Right>> {
+        StereoPair {
+            left: self.left.inmute(),
+            right: self.right.inmute(),
+        }
+    }
+    ///Instantiate left and right commands.
+    pub const fn into_commands(self) -> [Command<()>; 2] {
+        [self.left.into_command(), self.right.into_command()]
+    }
+}
+
+impl StereoPair<Invol<line_in::Left>, Invol<line_in::Right>> {
+    ///Writes raw bits to the field of both channels.
+    #[must_use]
+    pub const fn bits(self, value: u8) -> LineInPair {
+        StereoPair {
+            left: self.left.bits(value),
+            right: self.right.bits(value),
+        }
+    }
+    ///Set volume of both channels from a dB representation.
+    #[must_use]
+    pub const fn db(self, volume: InVoldB) -> LineInPair {
+        StereoPair {
+            left: self.left.db(volume),
+            right: self.right.db(volume),
+        }
+    }
+}
+
+impl_pair_toggle_writer!(Inmute, LineIn, line_in::Left, line_in::Right);
+
+impl HeadphoneOutPair {
+    ///Instantiate builders for both headphone outputs.
+    pub const fn headphone_out() -> Self {
+        StereoPair {
+            left: left_headphone_out(),
+            right: right_headphone_out(),
+        }
+    }
+    ///Headphone output commands carrying the same volume and zero cross detection setting.
+    ///
+    ///Both commands come from the same pair of writes, so they only differ by their address.
+    ///Other fields are left to their reset values.
+    pub const fn symmetric(volume: HpVoldB, zero_cross: bool) -> [Command<()>; 2] {
+        Self::headphone_out()
+            .hpvol()
+            .db(volume)
+            .zcen()
+            .bit(zero_cross)
+            .into_commands()
+    }
+    pub const fn hpvol(
+        self,
+    ) -> StereoPair<Hpvol<headphone_out::Left>, Hpvol<headphone_out::Right>> {
+        StereoPair {
+            left: self.left.hpvol(),
+            right: self.right.hpvol(),
+        }
+    }
+    pub const fn zcen(self) -> StereoPair<Zcen<headphone_out::Left>, Zcen<headphone_out::Right>> {
+        StereoPair {
+            left: self.left.zcen(),
+            right: self.right.zcen(),
+        }
+    }
+    ///Instantiate left and right commands.
+    pub const fn into_commands(self) -> [Command<()>; 2] {
+        [self.left.into_command(), self.right.into_command()]
+    }
+}
+
+impl StereoPair<Hpvol<headphone_out::Left>, Hpvol<headphone_out::Right>> {
+    ///Writes raw bits to the field of both channels.
+    #[must_use]
+    pub const fn bits(self, value: u8) -> HeadphoneOutPair {
+        StereoPair {
+            left: self.left.bits(value),
+            right: self.right.bits(value),
+        }
+    }
+    ///Set volume of both channels from a dB representation.
+    #[must_use]
+    pub const fn db(self, volume: HpVoldB) -> HeadphoneOutPair {
+        StereoPair {
+            left: self.left.db(volume),
+            right: self.right.db(volume),
+        }
+    }
+}
+
+impl_pair_toggle_writer!(
+    Zcen,
+    HeadphoneOut,
+    headphone_out::Left,
+    headphone_out::Right
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn line_in_pair() {
+        let [left, right] = StereoPair::line_in()
+            .invol()
+            .bits(0x10)
+            .inmute()
+            .disable()
+            .into_commands();
+        let test = [left.into_raw(), right.into_raw()];
+        let expected = [0b0_0001_0000, 0b1 << 9 | 0b0_0001_0000];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn headphone_out_pair() {
+        let [left, right] = StereoPair::headphone_out()
+            .hpvol()
+            .db(HpVoldB::N6DB)
+            .zcen()
+            .enable()
+            .into_commands();
+        let test = [left.into_raw(), right.into_raw()];
+        let expected = [0b10 << 9 | 0b0_1111_0011, 0b11 << 9 | 0b0_1111_0011];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn symmetric() {
+        for &zero_cross in &[false, true] {
+            for &volume in &[HpVoldB::MUTE, HpVoldB::N6DB, HpVoldB::P0DB] {
+                let [left, right] = StereoPair::symmetric(volume, zero_cross);
+                let test = left.into_raw() ^ right.into_raw();
+                let expected = (2 ^ 3) << 9;
+                assert!(
+                    test == expected,
+                    "Got {:#b}, expected {:#b}",
+                    test,
+                    expected
+                );
+                let test = left.value() >> 7 & 1 == 1;
+                assert!(test == zero_cross, "Got {}, expected {}", test, zero_cross);
+            }
+        }
+    }
+}
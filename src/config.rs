@@ -0,0 +1,210 @@
+//! Declarative configuration of the whole codec.
+//!
+//! [`Wm8731Config`] describes the codec state, it's lowered to commands with the usual builders.
+//!
+//! ## Example
+//! ```
+//! # use wm8731_alt::prelude::*;
+//! # use wm8731_alt::Wm8731;
+//! # use wm8731_alt::interface::WriteFrame;
+//! # use wm8731_alt::command::sampling::Mclk12M288;
+//! # use wm8731_alt::config::{ApplyError, Wm8731Config};
+//! # fn init<I: WriteFrame>(codec: &mut Wm8731<I>) -> Result<(), ApplyError<I::Error>> {
+//! let mut config = Wm8731Config::new(
+//!     sampling_with_mclk(Mclk12M288)
+//!         .sample_rate()
+//!         .adc48k_dac48k(),
+//! );
+//! config.headphone_volume = HpVoldB::N6DB;
+//! codec.apply(&config)
+//! # }
+//! ```
+
+use crate::command::analogue_audio_path::InselV;
+use crate::command::digital_audio_interface::{FormatV, IncoherentFormat, IwlV, MsV};
+use crate::command::headphone_out::HpVoldB;
+use crate::command::line_in::InVoldB;
+use crate::command::power_down::PowerDown;
+use crate::command::sampling::state_marker::SrValid;
+use crate::command::sampling::Sampling;
+use crate::command::*;
+use crate::interface::Frame;
+
+///Error returned by [`Wm8731::apply`](crate::Wm8731::apply).
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum ApplyError<E> {
+    ///The format and word length combination isn't supported by the codec, nothing was sent.
+    Incoherent(IncoherentFormat),
+    ///A frame couldn't be sent.
+    Interface(E),
+}
+
+impl<E> From<IncoherentFormat> for ApplyError<E> {
+    fn from(e: IncoherentFormat) -> Self {
+        ApplyError::Incoherent(e)
+    }
+}
+
+///Whole codec configuration.
+///
+///Both channels share the same volumes. The DAC is always selected and unmuted, bypass and
+///sidetone are disabled.
+#[derive(Debug)]
+pub struct Wm8731Config<MCLK> {
+    ///Sampling rates, for a given master clock.
+    pub sampling: Sampling<(MCLK, SrValid)>,
+    ///Digital audio interface format.
+    pub format: FormatV,
+    ///Digital audio interface word length. 32 bits isn't available with the right justified
+    ///format.
+    pub word_length: IwlV,
+    ///Digital audio interface master or slave mode.
+    pub mode: MsV,
+    ///ADC input source.
+    pub input: InselV,
+    ///Line input volume.
+    pub line_in_volume: InVoldB,
+    ///Headphone output volume.
+    pub headphone_volume: HpVoldB,
+    ///Power state once the configuration is applied.
+    pub power: PowerDown,
+}
+
+impl<MCLK> Copy for Wm8731Config<MCLK> {}
+
+impl<MCLK> Clone for Wm8731Config<MCLK> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<MCLK> Wm8731Config<MCLK> {
+    ///Instantiate a configuration with the given sampling rates.
+    ///
+    ///Other fields default to a 16 bits I2S slave interface, line input at 0dB, headphone output
+    ///at 0dB and every block powered up except the microphone.
+    pub const fn new(sampling: Sampling<(MCLK, SrValid)>) -> Self {
+        Self {
+            sampling,
+            format: FormatV::I2s,
+            word_length: IwlV::Iwl16bits,
+            mode: MsV::Slave,
+            input: InselV::Line,
+            line_in_volume: InVoldB::P0DB,
+            headphone_volume: HpVoldB::P0DB,
+            power: PowerDown::power_up_analog()
+                .micpd()
+                .enable()
+                .outpd()
+                .disable(),
+        }
+    }
+
+    ///Lower the configuration to commands, in a pop free order.
+    ///
+    ///Fails if the format and word length combination isn't supported by the codec, that is 32
+    ///bits word length with the right justified format.
+    ///
+    ///Registers are written in this order:
+    /// 1. Power down: requested power state, with outputs powered down.
+    /// 2. Left line in, with `inboth` set: both line inputs unmuted.
+    /// 3. Left headphone out, with `hpboth` set.
+    /// 4. Analogue audio path: DAC selected, bypass disabled.
+    /// 5. Digital audio path: DAC unmuted.
+    /// 6. Digital audio interface.
+    /// 7. Sampling.
+    /// 8. Active control: digital audio interface activated.
+    /// 9. Power down: requested power state.
+    pub fn to_commands(&self) -> Result<[Frame; 9], IncoherentFormat> {
+        let interface = digital_audio_interface()
+            .format()
+            .variant(self.format)
+            .iwl()
+            .variant(self.word_length)
+            .ms()
+            .variant(self.mode)
+            .try_into_command()?;
+        Ok([
+            self.power.outpd().enable().into_command().into(),
+            left_line_in()
+                .invol()
+                .db(self.line_in_volume)
+                .inmute()
+                .disable()
+                .inboth()
+                .enable()
+                .into_command()
+                .into(),
+            left_headphone_out()
+                .hpvol()
+                .db(self.headphone_volume)
+                .hpboth()
+                .enable()
+                .into_command()
+                .into(),
+            analogue_audio_path()
+                .insel()
+                .variant(self.input)
+                .dacsel()
+                .select()
+                .bypass()
+                .disable()
+                .into_command()
+                .into(),
+            digital_audio_path().dacmu().disable().into_command().into(),
+            interface.into(),
+            self.sampling.into_command().into(),
+            active_control().active().into_command().into(),
+            self.power.into_command().into(),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::sampling::Mclk12M288;
+    #[test]
+    fn same_as_init_default() {
+        let config =
+            Wm8731Config::new(sampling_with_mclk(Mclk12M288).sample_rate().adc48k_dac48k());
+        let test = config.to_commands();
+        let expected = Ok(crate::sequence::init_default());
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn fields_to_bits() {
+        let mut config =
+            Wm8731Config::new(sampling_with_mclk(Mclk12M288).sample_rate().adc96k_dac96k());
+        config.format = FormatV::LeftJustified;
+        config.word_length = IwlV::Iwl20bits;
+        config.mode = MsV::Master;
+        config.input = InselV::Microphone;
+        config.line_in_volume = InVoldB::N6DB;
+        config.headphone_volume = HpVoldB::N6DB;
+        let test = config.to_commands().unwrap().map(u16::from);
+        let expected = [
+            0b1_0000_0000 | 0b1_0011,
+            0b10 << 9 | 0b1_0000_0000 | 0b111_0011,
+            0b100 << 9 | 0b1_0110,
+            0b111 << 9 | 0b100_0101,
+            0b1000 << 9 | 0b1_1100,
+        ];
+        let test = [test[1], test[2], test[3], test[5], test[6]];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn incoherent_format() {
+        let mut config =
+            Wm8731Config::new(sampling_with_mclk(Mclk12M288).sample_rate().adc48k_dac48k());
+        config.format = FormatV::RigthJustified;
+        config.word_length = IwlV::Iwl32bits;
+        let test = config.to_commands();
+        let expected = Err(IncoherentFormat);
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        config.word_length = IwlV::Iwl24bits;
+        let test = config.to_commands().map(|frames| u16::from(frames[5]));
+        let expected = Ok(0b111 << 9 | 0b1000);
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+}
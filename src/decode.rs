@@ -0,0 +1,410 @@
+//! Decode raw control words back into register fields.
+//!
+//! This is mainly a debugging help, for example to interpret a captured bus trace.
+//!
+//! ## Example
+//! ```
+//! # use wm8731_alt::prelude::*;
+//! # use wm8731_alt::interface::Frame;
+//! # use wm8731_alt::decode::DecodedRegister;
+//! let frame: Frame = power_down().poweroff().disable().into_command().into();
+//! if let DecodedRegister::PowerDown(fields) = frame.decode() {
+//!     assert!(!fields.poweroff);
+//! }
+//! ```
+
+use crate::command::analogue_audio_path::{DacselV, InselV};
+use crate::command::digital_audio_interface::{FormatV, IwlV, MsV};
+use crate::command::digital_audio_path::{DeempV, HporV};
+use crate::registers;
+
+///Register content decoded from a control word.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodedRegister {
+    LeftLineIn(LineInFields),
+    RightLineIn(LineInFields),
+    LeftHeadphoneOut(HeadphoneOutFields),
+    RightHeadphoneOut(HeadphoneOutFields),
+    AnalogueAudioPath(AnalogueAudioPathFields),
+    DigitalAudioPath(DigitalAudioPathFields),
+    PowerDown(PowerDownFields),
+    DigitalAudioInterface(DigitalAudioInterfaceFields),
+    Sampling(SamplingFields),
+    ActiveControl(ActiveControlFields),
+    Reset,
+    ///The address doesn't correspond to any known register.
+    Unknown {
+        address: u8,
+        value: u16,
+    },
+}
+
+///Fields of the left and right line in registers.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LineInFields {
+    pub invol: u8,
+    pub inmute: bool,
+    pub inboth: bool,
+}
+
+///Fields of the left and right headphone out registers.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HeadphoneOutFields {
+    pub hpvol: u8,
+    pub zcen: bool,
+    pub hpboth: bool,
+}
+
+///Fields of the analogue audio path register.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AnalogueAudioPathFields {
+    pub micboost: bool,
+    pub mutemic: bool,
+    pub insel: InselV,
+    pub bypass: bool,
+    pub dacsel: DacselV,
+    pub sidetone: bool,
+    pub sideatt: u8,
+}
+
+///Fields of the digital audio path register.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DigitalAudioPathFields {
+    pub adchpd: bool,
+    pub deemp: DeempV,
+    pub dacmu: bool,
+    pub hpor: HporV,
+}
+
+///Fields of the power down register.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerDownFields {
+    pub lineinpd: bool,
+    pub micpd: bool,
+    pub adcpd: bool,
+    pub dacpd: bool,
+    pub outpd: bool,
+    pub oscpd: bool,
+    pub clkoutpd: bool,
+    pub poweroff: bool,
+}
+
+///Fields of the digital audio interface register.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DigitalAudioInterfaceFields {
+    pub format: FormatV,
+    pub iwl: IwlV,
+    pub lrp: bool,
+    pub lrswap: bool,
+    pub ms: MsV,
+    pub bclkinv: bool,
+}
+
+///Fields of the sampling register.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SamplingFields {
+    ///`true` for USB mode, `false` for normal mode.
+    pub usb: bool,
+    pub bosr: bool,
+    pub sr: u8,
+    pub clkidiv2: bool,
+    pub clkodiv2: bool,
+}
+
+///Fields of the active control register.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ActiveControlFields {
+    pub active: bool,
+}
+
+const fn bit(value: u16, pos: u8) -> bool {
+    value & (1 << pos) != 0
+}
+
+const fn bits(value: u16, length: u8, shift: u8) -> u8 {
+    ((value >> shift) & !((!0) << length)) as u8
+}
+
+pub(crate) const fn line_in_fields(value: u16) -> LineInFields {
+    LineInFields {
+        invol: bits(value, 5, 0),
+        inmute: bit(value, 7),
+        inboth: bit(value, 8),
+    }
+}
+
+pub(crate) const fn headphone_out_fields(value: u16) -> HeadphoneOutFields {
+    HeadphoneOutFields {
+        hpvol: bits(value, 7, 0),
+        zcen: bit(value, 7),
+        hpboth: bit(value, 8),
+    }
+}
+
+pub(crate) fn analogue_audio_path_fields(value: u16) -> AnalogueAudioPathFields {
+    AnalogueAudioPathFields {
+        micboost: bit(value, 0),
+        mutemic: bit(value, 1),
+        insel: InselV::from(bit(value, 2)),
+        bypass: bit(value, 3),
+        dacsel: DacselV::from(bit(value, 4)),
+        sidetone: bit(value, 5),
+        sideatt: bits(value, 2, 6),
+    }
+}
+
+pub(crate) const fn digital_audio_path_fields(value: u16) -> DigitalAudioPathFields {
+    DigitalAudioPathFields {
+        adchpd: bit(value, 0),
+        deemp: match bits(value, 2, 1) {
+            0b00 => DeempV::Disable,
+            0b01 => DeempV::F32k,
+            0b10 => DeempV::F44k1,
+            _ => DeempV::F48k,
+        },
+        dacmu: bit(value, 3),
+        hpor: if bit(value, 4) {
+            HporV::StoreOffset
+        } else {
+            HporV::ClearOffset
+        },
+    }
+}
+
+pub(crate) const fn power_down_fields(value: u16) -> PowerDownFields {
+    PowerDownFields {
+        lineinpd: bit(value, 0),
+        micpd: bit(value, 1),
+        adcpd: bit(value, 2),
+        dacpd: bit(value, 3),
+        outpd: bit(value, 4),
+        oscpd: bit(value, 5),
+        clkoutpd: bit(value, 6),
+        poweroff: bit(value, 7),
+    }
+}
+
+pub(crate) const fn digital_audio_interface_fields(value: u16) -> DigitalAudioInterfaceFields {
+    DigitalAudioInterfaceFields {
+        format: match bits(value, 2, 0) {
+            0b00 => FormatV::RigthJustified,
+            0b01 => FormatV::LeftJustified,
+            0b10 => FormatV::I2s,
+            _ => FormatV::Dsp,
+        },
+        iwl: match bits(value, 2, 2) {
+            0b00 => IwlV::Iwl16bits,
+            0b01 => IwlV::Iwl20bits,
+            0b10 => IwlV::Iwl24bits,
+            _ => IwlV::Iwl32bits,
+        },
+        lrp: bit(value, 4),
+        lrswap: bit(value, 5),
+        ms: if bit(value, 6) {
+            MsV::Master
+        } else {
+            MsV::Slave
+        },
+        bclkinv: bit(value, 7),
+    }
+}
+
+pub(crate) const fn sampling_fields(value: u16) -> SamplingFields {
+    SamplingFields {
+        usb: bit(value, 0),
+        bosr: bit(value, 1),
+        sr: bits(value, 4, 2),
+        clkidiv2: bit(value, 6),
+        clkodiv2: bit(value, 7),
+    }
+}
+
+pub(crate) const fn active_control_fields(value: u16) -> ActiveControlFields {
+    ActiveControlFields {
+        active: bit(value, 0),
+    }
+}
+
+///Decode a raw 16 bits control word.
+pub fn decode(word: u16) -> DecodedRegister {
+    let address = (word >> 9) as u8;
+    let value = word & 0x1FF;
+    match address {
+        registers::LEFT_LINE_IN => DecodedRegister::LeftLineIn(line_in_fields(value)),
+        registers::RIGHT_LINE_IN => DecodedRegister::RightLineIn(line_in_fields(value)),
+        registers::LEFT_HEADPHONE_OUT => {
+            DecodedRegister::LeftHeadphoneOut(headphone_out_fields(value))
+        }
+        registers::RIGHT_HEADPHONE_OUT => {
+            DecodedRegister::RightHeadphoneOut(headphone_out_fields(value))
+        }
+        registers::ANALOGUE_AUDIO_PATH => {
+            DecodedRegister::AnalogueAudioPath(analogue_audio_path_fields(value))
+        }
+        registers::DIGITAL_AUDIO_PATH => {
+            DecodedRegister::DigitalAudioPath(digital_audio_path_fields(value))
+        }
+        registers::POWER_DOWN => DecodedRegister::PowerDown(power_down_fields(value)),
+        registers::DIGITAL_AUDIO_INTERFACE => {
+            DecodedRegister::DigitalAudioInterface(digital_audio_interface_fields(value))
+        }
+        registers::SAMPLING => DecodedRegister::Sampling(sampling_fields(value)),
+        registers::ACTIVE_CONTROL => DecodedRegister::ActiveControl(active_control_fields(value)),
+        registers::RESET => DecodedRegister::Reset,
+        _ => DecodedRegister::Unknown { address, value },
+    }
+}
+
+///Difference between two control words at the same position, see [`diff_registers`].
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterDiff {
+    ///Position of the control words in the compared slices.
+    pub position: usize,
+    ///Expected control word, `None` if the expected slice is shorter.
+    pub expected: Option<u16>,
+    ///Actual control word, `None` if the actual slice is shorter.
+    pub got: Option<u16>,
+}
+
+impl RegisterDiff {
+    ///Return the address of the expected register, or of the actual one if there is no expected
+    ///word.
+    pub fn address(&self) -> u8 {
+        (self.expected.or(self.got).unwrap_or(0) >> 9) as u8
+    }
+    ///Return the name of the register, see [`address`](Self::address).
+    pub fn register_name(&self) -> &'static str {
+        match self.address() {
+            registers::LEFT_LINE_IN => "left line in",
+            registers::RIGHT_LINE_IN => "right line in",
+            registers::LEFT_HEADPHONE_OUT => "left headphone out",
+            registers::RIGHT_HEADPHONE_OUT => "right headphone out",
+            registers::ANALOGUE_AUDIO_PATH => "analogue audio path",
+            registers::DIGITAL_AUDIO_PATH => "digital audio path",
+            registers::POWER_DOWN => "power down",
+            registers::DIGITAL_AUDIO_INTERFACE => "digital audio interface",
+            registers::SAMPLING => "sampling",
+            registers::ACTIVE_CONTROL => "active control",
+            registers::RESET => "reset",
+            _ => "unknown",
+        }
+    }
+    ///Return the bits differing between both control words, address included. A missing word
+    ///counts as zero.
+    pub fn changed_bits(&self) -> u16 {
+        self.expected.unwrap_or(0) ^ self.got.unwrap_or(0)
+    }
+    ///Return both words decoded, to compare them field by field.
+    pub fn decoded(&self) -> (Option<DecodedRegister>, Option<DecodedRegister>) {
+        (self.expected.map(decode), self.got.map(decode))
+    }
+}
+
+///Compare control words position by position, yielding only the differing ones.
+///
+///Extra words of the longer slice are reported with the other side set to `None`.
+///
+///## Example
+///```
+///# use wm8731_alt::decode::diff_registers;
+///let expected = [0b0110 << 9 | 0b1_1111, 0b1001 << 9 | 0b1];
+///let got = [0b0110 << 9 | 0b1_1111, 0b1001 << 9];
+///let diff = diff_registers(&expected, &got).next().unwrap();
+///assert_eq!(diff.register_name(), "active control");
+///assert_eq!(diff.changed_bits(), 0b1);
+///```
+pub fn diff_registers<'a>(
+    expected: &'a [u16],
+    got: &'a [u16],
+) -> impl Iterator<Item = RegisterDiff> + 'a {
+    let len = core::cmp::max(expected.len(), got.len());
+    (0..len).filter_map(move |position| {
+        let diff = RegisterDiff {
+            position,
+            expected: expected.get(position).copied(),
+            got: got.get(position).copied(),
+        };
+        if diff.expected == diff.got {
+            None
+        } else {
+            Some(diff)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    use crate::interface::Frame;
+
+    #[test]
+    fn decode_frames() {
+        let frame: Frame = left_line_in().into_command().into();
+        let expected = DecodedRegister::LeftLineIn(LineInFields {
+            invol: 0b10111,
+            inmute: true,
+            inboth: false,
+        });
+        let test = frame.decode();
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let frame: Frame = sampling()
+            .usb_normal()
+            .usb()
+            .sr()
+            .sr_0b0111()
+            .into_command()
+            .into();
+        let expected = DecodedRegister::Sampling(SamplingFields {
+            usb: true,
+            bosr: false,
+            sr: 0b0111,
+            clkidiv2: false,
+            clkodiv2: false,
+        });
+        let test = frame.decode();
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let frame: Frame = reset().into_command().into();
+        let test = frame.decode();
+        let expected = DecodedRegister::Reset;
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+
+    #[test]
+    fn debug_output() {
+        use std::format;
+        let test = format!("{:?}", active_control().active());
+        let expected = "ActiveControl(ActiveControlFields { active: true })";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+        let test = format!("{:?}", active_control().active().into_command());
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+    }
+    #[test]
+    fn diff() {
+        let expected = [0b0110 << 9 | 0b1_1111, 0b0101 << 9 | 0b1000];
+        let got = [0b0110 << 9 | 0b1_0111, 0b0101 << 9 | 0b1000, 0b1001 << 9];
+        let mut diffs = diff_registers(&expected, &got);
+        let test = diffs
+            .next()
+            .map(|d| (d.position, d.register_name(), d.changed_bits()));
+        let expected = Some((0, "power down", 0b1000));
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = diffs
+            .next()
+            .map(|d| (d.position, d.register_name(), d.expected));
+        let expected = Some((2, "active control", None));
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = diffs.next();
+        assert!(test.is_none(), "Got {:?}, expected None", test);
+    }
+}
@@ -3,14 +3,155 @@ use core::marker::PhantomData;
 use embedded_hal::blocking::{i2c, spi};
 use embedded_hal::digital::v2::OutputPin;
 
+use crate::decode::{self, DecodedRegister};
+use crate::registers;
 use crate::Command;
 
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "async")]
+pub use asynch::*;
+#[cfg(feature = "eh1")]
+pub mod eh1;
+mod retry;
+pub use retry::*;
+mod tee;
+pub use tee::*;
+mod verifying;
+pub use verifying::*;
+#[cfg(any(test, feature = "std"))]
+mod recording;
+#[cfg(any(test, feature = "std"))]
+pub use recording::*;
+#[cfg(any(test, feature = "std"))]
+mod simulated;
+#[cfg(any(test, feature = "std"))]
+pub use simulated::*;
+
 ///Represent a frame sended through I2C or SPI interface.
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+///
+///Frames are ordered by their raw control word, that is by register address then content.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Frame {
     data: u16,
 }
 
+impl Frame {
+    ///Build a frame from a register address and a 9 bits register content.
+    ///
+    ///In debug builds, panics if the address is above 15 or the value doesn't fit in 9 bits.
+    ///
+    ///## Example
+    ///```
+    ///# use wm8731_alt::prelude::*;
+    ///# use wm8731_alt::interface::Frame;
+    ///let frame = unsafe { Frame::from_parts(9, 0b1) };
+    ///assert_eq!(frame, active_control().active().into_command().into());
+    ///```
+    ///
+    ///# Safety
+    ///
+    ///The frame is sent as is, it may target a reserved register or contain an invalid bit
+    ///combination. Please read the datasheet.
+    pub const unsafe fn from_parts(addr: u8, value: u16) -> Frame {
+        debug_assert!(addr <= 15, "address out of range");
+        debug_assert!(value <= 0x1FF, "value doesn't fit in 9 bits");
+        Frame {
+            data: (addr as u16) << 9 | value & 0x1FF,
+        }
+    }
+    ///Decode the frame into register fields.
+    pub fn decode(self) -> DecodedRegister {
+        decode::decode(self.data)
+    }
+}
+
+///Remove frames overwritten by a later frame targeting the same register, last write wins.
+///
+///Kept frames are moved at the beginning of the slice, in their original order, and their count is
+///returned. A reset frame overwrites every register, so frames are never merged across it. A frame
+///with the BOTH bit set also writes the other channel, so it's kept until both channels are
+///overwritten.
+///
+///## Example
+///```
+///# use wm8731_alt::prelude::*;
+///# use wm8731_alt::interface::{dedup_last, Frame};
+///let mut frames: [Frame; 3] = [
+///    power_down().into_command().into(),
+///    active_control().active().into_command().into(),
+///    power_down().poweroff().disable().into_command().into(),
+///];
+///let len = dedup_last(&mut frames);
+///assert_eq!(len, 2);
+///```
+pub fn dedup_last(frames: &mut [Frame]) -> usize {
+    let address = |frame: &Frame| (frame.data >> 9) as u8;
+    let mut len = 0;
+    for i in 0..frames.len() {
+        let written_later = |addr: u8| {
+            frames[i + 1..]
+                .iter()
+                .take_while(|&next| address(next) != registers::RESET)
+                .any(|next| address(next) == addr)
+        };
+        let addr = address(&frames[i]);
+        let overwritten = written_later(addr)
+            && registers::both_target(addr, frames[i].data & 0x1FF).map_or(true, written_later);
+        if !overwritten {
+            frames[len] = frames[i];
+            len += 1;
+        }
+    }
+    len
+}
+
+/// Order of the two bytes of a frame on a 8 bits bus.
+///
+/// The WM8731 itself wants the control word MSB first, that is [`MsbFirst`], the default of every
+/// interface. [`LsbFirst`] is only for wire-level adapters swapping bytes between the MCU and the
+/// codec.
+pub trait ByteOrder {
+    ///Split the control word into the bytes to write, in wire order.
+    fn to_bytes(data: u16) -> [u8; 2];
+}
+
+/// Most significant byte first, as expected by the codec.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct MsbFirst;
+
+impl ByteOrder for MsbFirst {
+    fn to_bytes(data: u16) -> [u8; 2] {
+        data.to_be_bytes()
+    }
+}
+
+/// Least significant byte first, for adapters swapping bytes on the wire.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LsbFirst;
+
+impl ByteOrder for LsbFirst {
+    fn to_bytes(data: u16) -> [u8; 2] {
+        data.to_le_bytes()
+    }
+}
+
+impl Frame {
+    ///Split the frame into bytes in the given wire order.
+    ///
+    ///## Example
+    ///```
+    ///# use wm8731_alt::interface::{Frame, LsbFirst, MsbFirst};
+    ///let frame = unsafe { Frame::from_parts(9, 0b1) };
+    ///assert_eq!(frame.to_bytes::<MsbFirst>(), [0x12, 0x01]);
+    ///assert_eq!(frame.to_bytes::<LsbFirst>(), [0x01, 0x12]);
+    ///```
+    pub fn to_bytes<O: ByteOrder>(self) -> [u8; 2] {
+        O::to_bytes(self.data)
+    }
+}
+
 impl From<Frame> for [u8; 2] {
     ///Allow to convert frame to an array directly usable with SPI and I2C abstraction from embedded-hal.
     fn from(frame: Frame) -> [u8; 2] {
@@ -32,6 +173,13 @@ impl From<Frame> for u16 {
     }
 }
 
+impl<T> From<Command<T>> for [u8; 2] {
+    ///Allow to convert a command to an array directly usable with SPI and I2C abstraction from embedded-hal.
+    fn from(cmd: Command<T>) -> [u8; 2] {
+        Frame::from(cmd).into()
+    }
+}
+
 impl<T> From<Command<T>> for Frame {
     ///Allow to convert frame to an array directly usable with SPI and I2C abstraction from embedded-hal.
     fn from(cmd: Command<T>) -> Frame {
@@ -41,42 +189,146 @@ impl<T> From<Command<T>> for Frame {
 
 /// Serial Interface abstraction for the wm8731 generic driver.
 pub trait WriteFrame {
-    fn send(&mut self, frame: Frame);
+    ///Error returned when a frame can't be sent.
+    type Error;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error>;
+}
+
+/// Interface discarding every frame, for dry runs and tests.
+///
+/// See `RecordingInterface` (`std` feature) to capture frames instead.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NullInterface;
+
+impl WriteFrame for NullInterface {
+    type Error = core::convert::Infallible;
+    fn send(&mut self, _frame: Frame) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// I2C address of the codec, selected by the level of the CSB pin.
+///
+/// Addresses are 7 bits. The I2C implementation shifts it left by one and appends the R/W bit,
+/// so `Csb0` gives a `0x34` write byte on the bus.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Wm8731Address {
+    ///CSB pin low.
+    Csb0 = 0x1A,
+    ///CSB pin high.
+    Csb1 = 0x1B,
+}
+
+impl From<Wm8731Address> for u8 {
+    fn from(address: Wm8731Address) -> u8 {
+        address as u8
+    }
 }
 
 /// I2C communication implementation using embedded-hal.
-pub struct I2CInterface<I2C> {
+///
+/// Any `i2c::Write` implementation is accepted, the bus doesn't need to be owned. On a shared bus,
+/// pass the proxy of the bus manager, for example the one of `shared_bus`.
+///
+/// Bytes are sent in `O` order, see [`ByteOrder`].
+pub struct I2CInterface<I2C, O = MsbFirst> {
     i2c: I2C,
     address: u8,
+    o: PhantomData<O>,
 }
 
 impl<I2C> I2CInterface<I2C>
 where
     I2C: i2c::Write,
 {
+    ///Instantiate the interface with a raw 7 bits address, for unusual boards.
+    ///
+    ///See [`Wm8731Address`] for the addresses of the codec.
     pub fn new(i2c: I2C, address: u8) -> Self {
-        Self { i2c, address }
+        Self {
+            i2c,
+            address,
+            o: PhantomData::<MsbFirst>,
+        }
+    }
+    ///Instantiate the interface with the address selected by the CSB pin.
+    pub fn with_address(i2c: I2C, address: Wm8731Address) -> Self {
+        Self::new(i2c, address.into())
+    }
+}
+
+impl<I2C, O> I2CInterface<I2C, O> {
+    ///Change the order of the bytes on the wire, for boards with a byte swapping adapter.
+    pub fn into_byte_order<O2: ByteOrder>(self) -> I2CInterface<I2C, O2> {
+        I2CInterface {
+            i2c: self.i2c,
+            address: self.address,
+            o: PhantomData::<O2>,
+        }
     }
     pub fn release(self) -> I2C {
         self.i2c
     }
 }
 
-impl<I2C> WriteFrame for I2CInterface<I2C>
+impl<I2C, O> WriteFrame for I2CInterface<I2C, O>
 where
     I2C: i2c::Write,
+    O: ByteOrder,
 {
-    fn send(&mut self, frame: Frame) {
-        let frame: [u8; 2] = frame.into();
-        let _ = self.i2c.write(self.address, &frame);
+    type Error = I2C::Error;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.i2c.write(self.address, &frame.to_bytes::<O>())
+    }
+}
+
+/// Error returned by SPI interfaces.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum SPIInterfaceError<SPI, CS> {
+    ///The SPI transfer failed.
+    Spi(SPI),
+    ///The chip select pin couldn't be driven.
+    Cs(CS),
+}
+
+impl<SPI: core::fmt::Debug, CS: core::fmt::Debug> core::fmt::Display
+    for SPIInterfaceError<SPI, CS>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SPIInterfaceError::Spi(e) => write!(f, "bus write error: {:?}", e),
+            SPIInterfaceError::Cs(e) => write!(f, "CS pin error: {:?}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<SPI: core::fmt::Debug, CS: core::fmt::Debug> std::error::Error for SPIInterfaceError<SPI, CS> {}
+
+/// Placeholder chip select for setups where CS is driven by hardware, for example a NSS pin
+/// managed by the SPI peripheral. Driving it does nothing.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NoCs;
+
+impl OutputPin for NoCs {
+    type Error = core::convert::Infallible;
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
     }
 }
 
 /// Generic blocking SPI communication implementation using embedded-hal.
-pub struct SPIInterface<SPI, CS, W> {
+///
+/// With 8 bits words, bytes are sent in `O` order, see [`ByteOrder`].
+pub struct SPIInterface<SPI, CS, W, O = MsbFirst> {
     spi: SPI,
     cs: CS,
     w: PhantomData<W>,
+    o: PhantomData<O>,
 }
 
 impl<SPI, CS, W> SPIInterface<SPI, CS, W> {
@@ -85,29 +337,103 @@ impl<SPI, CS, W> SPIInterface<SPI, CS, W> {
             spi,
             cs,
             w: PhantomData::<W>,
+            o: PhantomData::<MsbFirst>,
         }
     }
+}
+
+impl<SPI, CS, W, O> SPIInterface<SPI, CS, W, O> {
     pub fn release(self) -> SPI {
         self.spi
     }
 }
 
+impl<SPI, CS, O> SPIInterface<SPI, CS, u8, O> {
+    ///Change the order of the bytes on the wire, for boards with a byte swapping adapter.
+    pub fn into_byte_order<O2: ByteOrder>(self) -> SPIInterface<SPI, CS, u8, O2> {
+        SPIInterface {
+            spi: self.spi,
+            cs: self.cs,
+            w: PhantomData::<u8>,
+            o: PhantomData::<O2>,
+        }
+    }
+}
+
+impl<SPI, W> SPIInterface<SPI, NoCs, W> {
+    ///Instantiate an interface without chip select pin, when CS is driven by hardware.
+    pub fn new_hardware_cs(spi: SPI) -> Self {
+        Self::new(spi, NoCs)
+    }
+}
+
 /// 8 bits words SPI communication implementation using embedded-hal.
-pub type SPIInterfaceU8<SPI, CS> = SPIInterface<SPI, CS, u8>;
+pub type SPIInterfaceU8<SPI, CS, O = MsbFirst> = SPIInterface<SPI, CS, u8, O>;
 
 /// 16 bits words SPI communication implementation using embedded-hal.
 pub type SPIInterfaceU16<SPI, CS> = SPIInterface<SPI, CS, u16>;
 
-impl<SPI, CS> WriteFrame for SPIInterfaceU8<SPI, CS>
+impl<SPI, CS, O> SPIInterfaceU8<SPI, CS, O>
 where
     SPI: spi::Write<u8>,
     CS: OutputPin,
+    O: ByteOrder,
 {
-    fn send(&mut self, frame: Frame) {
-        let frame: [u8; 2] = frame.into();
-        let _ = self.cs.set_low();
-        let _ = self.spi.write(&frame);
-        let _ = self.cs.set_high();
+    ///Send several frames within a single chip select assertion, for example to daisy chained
+    ///codecs.
+    ///
+    ///CS is asserted once before the first frame and released after the last one, frames are
+    ///written back to back in between.
+    pub fn send_batch(
+        &mut self,
+        frames: &[Frame],
+    ) -> Result<(), SPIInterfaceError<<SPI as spi::Write<u8>>::Error, CS::Error>> {
+        self.cs.set_low().map_err(SPIInterfaceError::Cs)?;
+        let res = frames
+            .iter()
+            .try_for_each(|&frame| self.spi.write(&frame.to_bytes::<O>()))
+            .map_err(SPIInterfaceError::Spi);
+        self.cs.set_high().map_err(SPIInterfaceError::Cs)?;
+        res
+    }
+}
+
+impl<SPI, CS, O> WriteFrame for SPIInterfaceU8<SPI, CS, O>
+where
+    SPI: spi::Write<u8>,
+    CS: OutputPin,
+    O: ByteOrder,
+{
+    type Error = SPIInterfaceError<<SPI as spi::Write<u8>>::Error, CS::Error>;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.send_batch(core::slice::from_ref(&frame))
+    }
+}
+
+impl<SPI, CS> SPIInterfaceU16<SPI, CS>
+where
+    SPI: spi::Write<u16>,
+    CS: OutputPin,
+{
+    ///Send several frames within a single chip select assertion, for example to daisy chained
+    ///codecs.
+    ///
+    ///CS is asserted once before the first frame and released after the last one, frames are
+    ///written back to back in between.
+    pub fn send_batch(
+        &mut self,
+        frames: &[Frame],
+    ) -> Result<(), SPIInterfaceError<<SPI as spi::Write<u16>>::Error, CS::Error>> {
+        self.cs.set_low().map_err(SPIInterfaceError::Cs)?;
+        let res = frames
+            .iter()
+            .try_for_each(|&frame| {
+                let frame: [u16; 1] = frame.into();
+                self.spi.write(&frame)
+            })
+            .map_err(SPIInterfaceError::Spi);
+        self.cs.set_high().map_err(SPIInterfaceError::Cs)?;
+        res
     }
 }
 
@@ -116,10 +442,63 @@ where
     SPI: spi::Write<u16>,
     CS: OutputPin,
 {
-    fn send(&mut self, frame: Frame) {
-        let frame: [u16; 1] = frame.into();
-        let _ = self.cs.set_low();
-        let _ = self.spi.write(&frame);
-        let _ = self.cs.set_high();
+    type Error = SPIInterfaceError<<SPI as spi::Write<u16>>::Error, CS::Error>;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.send_batch(core::slice::from_ref(&frame))
+    }
+}
+
+/// SPI word size, for interfaces selecting it at runtime.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpiWordSize {
+    ///Frames are written as two 8 bits words, most significant first.
+    U8,
+    ///Frames are written as one 16 bits word.
+    U16,
+}
+
+/// Blocking SPI communication implementation with a word size selected at runtime.
+///
+/// Unlike [`SPIInterfaceU8`] and [`SPIInterfaceU16`], the word size is a value, for HAL whose SPI
+/// frame format is only known after runtime configuration. The SPI must support both sizes.
+pub struct SPIInterfaceAnyWidth<SPI, CS> {
+    spi: SPI,
+    cs: CS,
+    width: SpiWordSize,
+}
+
+impl<SPI, CS> SPIInterfaceAnyWidth<SPI, CS> {
+    pub fn new(spi: SPI, cs: CS, width: SpiWordSize) -> Self {
+        Self { spi, cs, width }
+    }
+    ///Return the word size used for the next frames.
+    pub fn width(&self) -> SpiWordSize {
+        self.width
+    }
+    ///Change the word size, for example after reconfiguring the SPI frame format.
+    pub fn set_width(&mut self, width: SpiWordSize) {
+        self.width = width;
+    }
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI, CS, E> WriteFrame for SPIInterfaceAnyWidth<SPI, CS>
+where
+    SPI: spi::Write<u8, Error = E> + spi::Write<u16, Error = E>,
+    CS: OutputPin,
+{
+    type Error = SPIInterfaceError<E, CS::Error>;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.cs.set_low().map_err(SPIInterfaceError::Cs)?;
+        let res = match self.width {
+            SpiWordSize::U8 => self.spi.write(&<[u8; 2]>::from(frame)),
+            SpiWordSize::U16 => self.spi.write(&<[u16; 1]>::from(frame)),
+        }
+        .map_err(SPIInterfaceError::Spi);
+        self.cs.set_high().map_err(SPIInterfaceError::Cs)?;
+        res
     }
 }
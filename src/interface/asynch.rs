@@ -0,0 +1,142 @@
+//! Asynchronous communication details, using embedded-hal-async.
+use embedded_hal_async::{i2c, spi};
+
+use super::{Frame, Wm8731Address};
+
+/// Asynchronous serial interface abstraction for the wm8731 generic driver.
+#[allow(async_fn_in_trait)]
+pub trait AsyncWriteFrame {
+    ///Error returned when a frame can't be sent.
+    type Error;
+    async fn send(&mut self, frame: Frame) -> Result<(), Self::Error>;
+}
+
+/// Asynchronous I2C communication implementation using embedded-hal-async.
+pub struct AsyncI2CInterface<I2C> {
+    i2c: I2C,
+    address: u8,
+}
+
+impl<I2C> AsyncI2CInterface<I2C>
+where
+    I2C: i2c::I2c,
+{
+    ///Instantiate the interface with a raw 7 bits address, for unusual boards.
+    ///
+    ///See [`Wm8731Address`] for the addresses of the codec.
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self { i2c, address }
+    }
+    ///Instantiate the interface with the address selected by the CSB pin.
+    pub fn with_address(i2c: I2C, address: Wm8731Address) -> Self {
+        Self::new(i2c, address.into())
+    }
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C> AsyncWriteFrame for AsyncI2CInterface<I2C>
+where
+    I2C: i2c::I2c,
+{
+    type Error = I2C::Error;
+    async fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        let frame: [u8; 2] = frame.into();
+        self.i2c.write(self.address, &frame).await
+    }
+}
+
+/// Asynchronous SPI communication implementation using embedded-hal-async.
+///
+/// Chip select and bus sharing are handled by the `SpiDevice` implementation.
+pub struct AsyncSPIInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> AsyncSPIInterface<SPI>
+where
+    SPI: spi::SpiDevice,
+{
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI> AsyncWriteFrame for AsyncSPIInterface<SPI>
+where
+    SPI: spi::SpiDevice,
+{
+    type Error = SPI::Error;
+    async fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        let frame: [u8; 2] = frame.into();
+        self.spi.write(&frame).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    use crate::test_utils::block_on;
+    use core::convert::Infallible;
+    use std::vec::Vec;
+
+    struct RecordI2c(Vec<(u8, Vec<u8>)>);
+    impl i2c::ErrorType for RecordI2c {
+        type Error = Infallible;
+    }
+    impl i2c::I2c for RecordI2c {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for op in operations {
+                if let i2c::Operation::Write(bytes) = op {
+                    self.0.push((address, bytes.to_vec()));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct RecordSpi(Vec<Vec<u8>>);
+    impl spi::ErrorType for RecordSpi {
+        type Error = Infallible;
+    }
+    impl spi::SpiDevice for RecordSpi {
+        async fn transaction(
+            &mut self,
+            operations: &mut [spi::Operation<'_, u8>],
+        ) -> Result<(), Self::Error> {
+            for op in operations {
+                if let spi::Operation::Write(words) = op {
+                    self.0.push(words.to_vec());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn i2c_send() {
+        let mut i2c_if =
+            AsyncI2CInterface::with_address(RecordI2c(Vec::new()), Wm8731Address::Csb1);
+        block_on(i2c_if.send(active_control().active().into())).unwrap();
+        let test = i2c_if.release().0;
+        let expected = [(0x1B, std::vec![0x12, 0x01])];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn spi_send() {
+        let mut spi_if = AsyncSPIInterface::new(RecordSpi(Vec::new()));
+        block_on(spi_if.send(active_control().active().into())).unwrap();
+        let test = spi_if.release().0;
+        let expected = [std::vec![0x12, 0x01]];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+}
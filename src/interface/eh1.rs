@@ -0,0 +1,608 @@
+//! Communication implementations using embedded-hal 1.0 traits.
+//!
+//! They mirror the embedded-hal 0.2 based implementations of the parent module.
+use core::marker::PhantomData;
+use embedded_hal_1::digital::{ErrorType, OutputPin};
+use embedded_hal_1::i2c;
+use embedded_hal_1::spi::{SpiBus, SpiDevice};
+
+use super::{
+    ByteOrder, Frame, MsbFirst, NoCs, SPIInterfaceError, SpiWordSize, Wm8731Address, WriteFrame,
+};
+
+/// I2C communication implementation using embedded-hal 1.0.
+///
+/// Bytes are sent in `O` order, see [`ByteOrder`].
+pub struct I2CInterface<I2C, O = MsbFirst> {
+    i2c: I2C,
+    address: u8,
+    o: PhantomData<O>,
+}
+
+impl<I2C> I2CInterface<I2C>
+where
+    I2C: i2c::I2c,
+{
+    ///Instantiate the interface with a raw 7 bits address, for unusual boards.
+    ///
+    ///See [`Wm8731Address`] for the addresses of the codec.
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            o: PhantomData::<MsbFirst>,
+        }
+    }
+    ///Instantiate the interface with the address selected by the CSB pin.
+    pub fn with_address(i2c: I2C, address: Wm8731Address) -> Self {
+        Self::new(i2c, address.into())
+    }
+}
+
+impl<I2C, O> I2CInterface<I2C, O> {
+    ///Change the order of the bytes on the wire, for boards with a byte swapping adapter.
+    pub fn into_byte_order<O2: ByteOrder>(self) -> I2CInterface<I2C, O2> {
+        I2CInterface {
+            i2c: self.i2c,
+            address: self.address,
+            o: PhantomData::<O2>,
+        }
+    }
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C, O> WriteFrame for I2CInterface<I2C, O>
+where
+    I2C: i2c::I2c,
+    O: ByteOrder,
+{
+    type Error = I2C::Error;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.i2c.write(self.address, &frame.to_bytes::<O>())
+    }
+}
+
+impl ErrorType for NoCs {
+    type Error = core::convert::Infallible;
+}
+
+impl OutputPin for NoCs {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Generic SPI communication implementation using embedded-hal 1.0 `SpiBus` and a chip select
+/// pin.
+///
+/// With 8 bits words, bytes are sent in `O` order, see [`ByteOrder`].
+pub struct SPIInterface<SPI, CS, W, O = MsbFirst> {
+    spi: SPI,
+    cs: CS,
+    w: PhantomData<W>,
+    o: PhantomData<O>,
+}
+
+impl<SPI, CS, W> SPIInterface<SPI, CS, W> {
+    pub fn new(spi: SPI, cs: CS) -> Self {
+        Self {
+            spi,
+            cs,
+            w: PhantomData::<W>,
+            o: PhantomData::<MsbFirst>,
+        }
+    }
+}
+
+impl<SPI, CS, W, O> SPIInterface<SPI, CS, W, O> {
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI, CS, O> SPIInterface<SPI, CS, u8, O> {
+    ///Change the order of the bytes on the wire, for boards with a byte swapping adapter.
+    pub fn into_byte_order<O2: ByteOrder>(self) -> SPIInterface<SPI, CS, u8, O2> {
+        SPIInterface {
+            spi: self.spi,
+            cs: self.cs,
+            w: PhantomData::<u8>,
+            o: PhantomData::<O2>,
+        }
+    }
+}
+
+impl<SPI, W> SPIInterface<SPI, NoCs, W> {
+    ///Instantiate an interface without chip select pin, when CS is driven by hardware.
+    pub fn new_hardware_cs(spi: SPI) -> Self {
+        Self::new(spi, NoCs)
+    }
+}
+
+/// 8 bits words SPI communication implementation using embedded-hal 1.0.
+pub type SPIInterfaceU8<SPI, CS, O = MsbFirst> = SPIInterface<SPI, CS, u8, O>;
+
+/// 16 bits words SPI communication implementation using embedded-hal 1.0.
+pub type SPIInterfaceU16<SPI, CS> = SPIInterface<SPI, CS, u16>;
+
+impl<SPI, CS, O> SPIInterfaceU8<SPI, CS, O>
+where
+    SPI: SpiBus<u8>,
+    CS: OutputPin,
+    O: ByteOrder,
+{
+    ///Send several frames within a single chip select assertion, for example to daisy chained
+    ///codecs.
+    ///
+    ///CS is asserted once before the first frame and released after the last one, frames are
+    ///written back to back in between.
+    pub fn send_batch(
+        &mut self,
+        frames: &[Frame],
+    ) -> Result<(), SPIInterfaceError<SPI::Error, CS::Error>> {
+        self.cs.set_low().map_err(SPIInterfaceError::Cs)?;
+        let res = frames
+            .iter()
+            .try_for_each(|&frame| self.spi.write(&frame.to_bytes::<O>()))
+            .and_then(|_| self.spi.flush())
+            .map_err(SPIInterfaceError::Spi);
+        self.cs.set_high().map_err(SPIInterfaceError::Cs)?;
+        res
+    }
+}
+
+impl<SPI, CS, O> WriteFrame for SPIInterfaceU8<SPI, CS, O>
+where
+    SPI: SpiBus<u8>,
+    CS: OutputPin,
+    O: ByteOrder,
+{
+    type Error = SPIInterfaceError<SPI::Error, CS::Error>;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.send_batch(core::slice::from_ref(&frame))
+    }
+}
+
+impl<SPI, CS> SPIInterfaceU16<SPI, CS>
+where
+    SPI: SpiBus<u16>,
+    CS: OutputPin,
+{
+    ///Send several frames within a single chip select assertion, for example to daisy chained
+    ///codecs.
+    ///
+    ///CS is asserted once before the first frame and released after the last one, frames are
+    ///written back to back in between.
+    pub fn send_batch(
+        &mut self,
+        frames: &[Frame],
+    ) -> Result<(), SPIInterfaceError<SPI::Error, CS::Error>> {
+        self.cs.set_low().map_err(SPIInterfaceError::Cs)?;
+        let res = frames
+            .iter()
+            .try_for_each(|&frame| {
+                let frame: [u16; 1] = frame.into();
+                self.spi.write(&frame)
+            })
+            .and_then(|_| self.spi.flush())
+            .map_err(SPIInterfaceError::Spi);
+        self.cs.set_high().map_err(SPIInterfaceError::Cs)?;
+        res
+    }
+}
+
+impl<SPI, CS> WriteFrame for SPIInterfaceU16<SPI, CS>
+where
+    SPI: SpiBus<u16>,
+    CS: OutputPin,
+{
+    type Error = SPIInterfaceError<SPI::Error, CS::Error>;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.send_batch(core::slice::from_ref(&frame))
+    }
+}
+
+/// SPI communication implementation using embedded-hal 1.0, with a word size selected at
+/// runtime.
+pub struct SPIInterfaceAnyWidth<SPI, CS> {
+    spi: SPI,
+    cs: CS,
+    width: SpiWordSize,
+}
+
+impl<SPI, CS> SPIInterfaceAnyWidth<SPI, CS> {
+    pub fn new(spi: SPI, cs: CS, width: SpiWordSize) -> Self {
+        Self { spi, cs, width }
+    }
+    ///Return the word size used for the next frames.
+    pub fn width(&self) -> SpiWordSize {
+        self.width
+    }
+    ///Change the word size, for example after reconfiguring the SPI frame format.
+    pub fn set_width(&mut self, width: SpiWordSize) {
+        self.width = width;
+    }
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI, CS> WriteFrame for SPIInterfaceAnyWidth<SPI, CS>
+where
+    SPI: SpiBus<u8> + SpiBus<u16>,
+    CS: OutputPin,
+{
+    type Error = SPIInterfaceError<SPI::Error, CS::Error>;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.cs.set_low().map_err(SPIInterfaceError::Cs)?;
+        let res = match self.width {
+            SpiWordSize::U8 => self.spi.write(&<[u8; 2]>::from(frame)),
+            SpiWordSize::U16 => self.spi.write(&<[u16; 1]>::from(frame)),
+        }
+        .and_then(|_| SpiBus::<u8>::flush(&mut self.spi))
+        .map_err(SPIInterfaceError::Spi);
+        self.cs.set_high().map_err(SPIInterfaceError::Cs)?;
+        res
+    }
+}
+
+/// SPI communication implementation using an embedded-hal 1.0 `SpiDevice`.
+///
+/// Chip select and bus sharing are handled by the `SpiDevice` implementation, this allows to use
+/// the codec on a shared bus, for example with `embedded-hal-bus`.
+pub struct SPIDeviceInterface<DEV> {
+    dev: DEV,
+}
+
+impl<DEV> SPIDeviceInterface<DEV>
+where
+    DEV: SpiDevice,
+{
+    pub fn new(dev: DEV) -> Self {
+        Self { dev }
+    }
+    pub fn release(self) -> DEV {
+        self.dev
+    }
+}
+
+impl<DEV> WriteFrame for SPIDeviceInterface<DEV>
+where
+    DEV: SpiDevice,
+{
+    type Error = DEV::Error;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        let frame: [u8; 2] = frame.into();
+        self.dev.write(&frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    use crate::interface::LsbFirst;
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+    use embedded_hal_1::digital;
+    use embedded_hal_1::spi::{self, ErrorKind};
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    enum Event {
+        CsLow,
+        CsHigh,
+        Write8(Vec<u8>),
+        Write16(Vec<u16>),
+        Flush,
+    }
+
+    type Log = Rc<RefCell<Vec<Event>>>;
+
+    #[derive(Debug, Eq, PartialEq, Copy, Clone)]
+    struct BusError;
+    impl i2c::Error for BusError {
+        fn kind(&self) -> i2c::ErrorKind {
+            i2c::ErrorKind::Other
+        }
+    }
+    impl spi::Error for BusError {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+    impl digital::Error for BusError {
+        fn kind(&self) -> digital::ErrorKind {
+            digital::ErrorKind::Other
+        }
+    }
+
+    struct RecordI2c(Vec<(u8, Vec<u8>)>);
+    impl i2c::ErrorType for RecordI2c {
+        type Error = Infallible;
+    }
+    impl i2c::I2c for RecordI2c {
+        fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            for op in operations {
+                if let i2c::Operation::Write(bytes) = op {
+                    self.0.push((address, bytes.to_vec()));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct BrokenI2c;
+    impl i2c::ErrorType for BrokenI2c {
+        type Error = BusError;
+    }
+    impl i2c::I2c for BrokenI2c {
+        fn transaction(
+            &mut self,
+            _address: u8,
+            _operations: &mut [i2c::Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            Err(BusError)
+        }
+    }
+
+    ///SPI bus logging writes, failing every write if `broken`.
+    struct RecordSpi {
+        log: Log,
+        broken: bool,
+    }
+    impl spi::ErrorType for RecordSpi {
+        type Error = BusError;
+    }
+    macro_rules! impl_record_spi {
+        ($word:ty, $event:ident) => {
+            impl SpiBus<$word> for RecordSpi {
+                fn read(&mut self, _words: &mut [$word]) -> Result<(), BusError> {
+                    Ok(())
+                }
+                fn write(&mut self, words: &[$word]) -> Result<(), BusError> {
+                    if self.broken {
+                        return Err(BusError);
+                    }
+                    self.log.borrow_mut().push(Event::$event(words.to_vec()));
+                    Ok(())
+                }
+                fn transfer(
+                    &mut self,
+                    _read: &mut [$word],
+                    _write: &[$word],
+                ) -> Result<(), BusError> {
+                    Ok(())
+                }
+                fn transfer_in_place(&mut self, _words: &mut [$word]) -> Result<(), BusError> {
+                    Ok(())
+                }
+                fn flush(&mut self) -> Result<(), BusError> {
+                    self.log.borrow_mut().push(Event::Flush);
+                    Ok(())
+                }
+            }
+        };
+    }
+    impl_record_spi!(u8, Write8);
+    impl_record_spi!(u16, Write16);
+
+    ///Chip select pin logging its level, failing if `broken`.
+    struct RecordPin {
+        log: Log,
+        broken: bool,
+    }
+    impl ErrorType for RecordPin {
+        type Error = BusError;
+    }
+    impl OutputPin for RecordPin {
+        fn set_low(&mut self) -> Result<(), BusError> {
+            if self.broken {
+                return Err(BusError);
+            }
+            self.log.borrow_mut().push(Event::CsLow);
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), BusError> {
+            if self.broken {
+                return Err(BusError);
+            }
+            self.log.borrow_mut().push(Event::CsHigh);
+            Ok(())
+        }
+    }
+
+    fn spi_and_pin(spi_broken: bool, pin_broken: bool) -> (Log, RecordSpi, RecordPin) {
+        let log = Log::default();
+        let spi = RecordSpi {
+            log: log.clone(),
+            broken: spi_broken,
+        };
+        let pin = RecordPin {
+            log: log.clone(),
+            broken: pin_broken,
+        };
+        (log, spi, pin)
+    }
+
+    ///SPI device recording written words per transaction, failing if `broken`.
+    struct RecordDevice {
+        writes: Vec<Vec<u8>>,
+        broken: bool,
+    }
+    impl spi::ErrorType for RecordDevice {
+        type Error = BusError;
+    }
+    impl SpiDevice for RecordDevice {
+        fn transaction(
+            &mut self,
+            operations: &mut [spi::Operation<'_, u8>],
+        ) -> Result<(), BusError> {
+            if self.broken {
+                return Err(BusError);
+            }
+            for op in operations {
+                if let spi::Operation::Write(words) = op {
+                    self.writes.push(words.to_vec());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn i2c_send() {
+        let mut i2c_if = I2CInterface::with_address(RecordI2c(Vec::new()), Wm8731Address::Csb1);
+        i2c_if.send(active_control().active().into()).unwrap();
+        let mut i2c_if = i2c_if.into_byte_order::<LsbFirst>();
+        i2c_if.send(active_control().active().into()).unwrap();
+        let test = i2c_if.release().0;
+        let expected = [(0x1B, std::vec![0x12, 0x01]), (0x1B, std::vec![0x01, 0x12])];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let mut i2c_if = I2CInterface::new(BrokenI2c, 0x1A);
+        let test = i2c_if.send(active_control().active().into());
+        assert!(
+            test == Err(BusError),
+            "Got {:?}, expected {:?}",
+            test,
+            BusError
+        );
+    }
+    #[test]
+    fn spi_u8_send_batch() {
+        let (log, spi, pin) = spi_and_pin(false, false);
+        let mut spi_if: SPIInterfaceU8<_, _> = SPIInterface::new(spi, pin);
+        let frames = [
+            power_down().poweroff().disable().into(),
+            active_control().active().into(),
+        ];
+        spi_if.send_batch(&frames).unwrap();
+        let mut spi_if = spi_if.into_byte_order::<LsbFirst>();
+        spi_if.send(active_control().active().into()).unwrap();
+        let test = log.take();
+        let expected = [
+            Event::CsLow,
+            Event::Write8(std::vec![0x0C, 0x1F]),
+            Event::Write8(std::vec![0x12, 0x01]),
+            Event::Flush,
+            Event::CsHigh,
+            Event::CsLow,
+            Event::Write8(std::vec![0x01, 0x12]),
+            Event::Flush,
+            Event::CsHigh,
+        ];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn spi_u16_send_batch() {
+        let (log, spi, pin) = spi_and_pin(false, false);
+        let mut spi_if: SPIInterfaceU16<_, _> = SPIInterface::new(spi, pin);
+        let frames = [
+            power_down().poweroff().disable().into(),
+            active_control().active().into(),
+        ];
+        spi_if.send_batch(&frames).unwrap();
+        let test = log.take();
+        let expected = [
+            Event::CsLow,
+            Event::Write16(std::vec![0x0C1F]),
+            Event::Write16(std::vec![0x1201]),
+            Event::Flush,
+            Event::CsHigh,
+        ];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn spi_errors() {
+        //CS is released on SPI error
+        let (log, spi, pin) = spi_and_pin(true, false);
+        let mut spi_if: SPIInterfaceU8<_, _> = SPIInterface::new(spi, pin);
+        let test = spi_if.send(active_control().active().into());
+        let expected = Err(SPIInterfaceError::Spi(BusError));
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = log.take();
+        let expected = [Event::CsLow, Event::CsHigh];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        //nothing is written if CS can't be asserted
+        let (log, spi, pin) = spi_and_pin(false, true);
+        let mut spi_if: SPIInterfaceU16<_, _> = SPIInterface::new(spi, pin);
+        let test = spi_if.send(active_control().active().into());
+        let expected = Err(SPIInterfaceError::Cs(BusError));
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = log.take();
+        assert!(test.is_empty(), "Got {:?}, expected no event", test);
+    }
+    #[test]
+    fn spi_any_width() {
+        let (log, spi, pin) = spi_and_pin(false, false);
+        let mut spi_if = SPIInterfaceAnyWidth::new(spi, pin, SpiWordSize::U8);
+        spi_if.send(active_control().active().into()).unwrap();
+        spi_if.set_width(SpiWordSize::U16);
+        spi_if.send(active_control().active().into()).unwrap();
+        let test = log.take();
+        let expected = [
+            Event::CsLow,
+            Event::Write8(std::vec![0x12, 0x01]),
+            Event::Flush,
+            Event::CsHigh,
+            Event::CsLow,
+            Event::Write16(std::vec![0x1201]),
+            Event::Flush,
+            Event::CsHigh,
+        ];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let (log, spi, pin) = spi_and_pin(true, false);
+        let mut spi_if = SPIInterfaceAnyWidth::new(spi, pin, SpiWordSize::U16);
+        let test = spi_if.send(active_control().active().into());
+        let expected = Err(SPIInterfaceError::Spi(BusError));
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = log.take();
+        let expected = [Event::CsLow, Event::CsHigh];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn spi_hardware_cs() {
+        let (log, spi, _) = spi_and_pin(false, false);
+        let mut spi_if: SPIInterfaceU8<_, _> = SPIInterface::new_hardware_cs(spi);
+        spi_if.send(active_control().active().into()).unwrap();
+        let test = log.take();
+        let expected = [Event::Write8(std::vec![0x12, 0x01]), Event::Flush];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn spi_device_send() {
+        let dev = RecordDevice {
+            writes: Vec::new(),
+            broken: false,
+        };
+        let mut spi_if = SPIDeviceInterface::new(dev);
+        spi_if
+            .send(power_down().poweroff().disable().into())
+            .unwrap();
+        let test = spi_if.release().writes;
+        let expected = [std::vec![0x0C, 0x1F]];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let dev = RecordDevice {
+            writes: Vec::new(),
+            broken: true,
+        };
+        let mut spi_if = SPIDeviceInterface::new(dev);
+        let test = spi_if.send(active_control().active().into());
+        assert!(
+            test == Err(BusError),
+            "Got {:?}, expected {:?}",
+            test,
+            BusError
+        );
+    }
+}
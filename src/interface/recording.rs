@@ -0,0 +1,54 @@
+#[cfg(feature = "async")]
+use super::AsyncWriteFrame;
+use super::{Frame, WriteFrame};
+use core::convert::Infallible;
+use std::vec::Vec;
+
+///Interface recording every sent frame instead of sending it, handy for testing.
+///
+///With the `async` feature, it's also an asynchronous interface completing immediately.
+///
+///## Example
+///```
+///# use wm8731_alt::prelude::*;
+///# use wm8731_alt::Wm8731;
+///# use wm8731_alt::interface::RecordingInterface;
+///let mut wm8731 = Wm8731::new(RecordingInterface::new()).unwrap();
+///wm8731.send(active_control().active().into_command()).unwrap();
+///assert_eq!(wm8731.release().frames(), &[0b1111 << 9, 0b1001 << 9 | 0b1]);
+///```
+#[derive(Debug, Default, Clone)]
+pub struct RecordingInterface {
+    frames: Vec<u16>,
+}
+
+impl RecordingInterface {
+    pub fn new() -> Self {
+        Self { frames: Vec::new() }
+    }
+    ///Return recorded frames, in sending order.
+    pub fn frames(&self) -> &[u16] {
+        &self.frames
+    }
+    ///Forget recorded frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
+
+impl WriteFrame for RecordingInterface {
+    type Error = Infallible;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.frames.push(frame.into());
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncWriteFrame for RecordingInterface {
+    type Error = Infallible;
+    async fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.frames.push(frame.into());
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use super::{Frame, WriteFrame};
+
+///Interface wrapper sending again a frame when the inner interface fails.
+///
+///A frame is sent at most `retries + 1` times, `delay` being called between attempts. The error of
+///the last attempt is returned.
+///
+///## Example
+///```
+///# use wm8731_alt::interface::{NullInterface, Retry};
+///# use wm8731_alt::Wm8731;
+///let interface = Retry::new(NullInterface, 3, || {
+///    //wait a bit, for example with an embedded-hal delay
+///});
+///let wm8731 = Wm8731::new(interface);
+///```
+pub struct Retry<W, D> {
+    inner: W,
+    retries: u8,
+    delay: D,
+}
+
+impl<W, D> Retry<W, D>
+where
+    W: WriteFrame,
+    D: FnMut(),
+{
+    pub fn new(inner: W, retries: u8, delay: D) -> Self {
+        Self {
+            inner,
+            retries,
+            delay,
+        }
+    }
+    ///Release the wrapped interface.
+    pub fn release(self) -> W {
+        self.inner
+    }
+}
+
+impl<W, D> WriteFrame for Retry<W, D>
+where
+    W: WriteFrame,
+    D: FnMut(),
+{
+    type Error = W::Error;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        let mut res = self.inner.send(frame);
+        for _ in 0..self.retries {
+            if res.is_ok() {
+                break;
+            }
+            (self.delay)();
+            res = self.inner.send(frame);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Flaky {
+        failures: u8,
+        sent: u8,
+    }
+    impl WriteFrame for Flaky {
+        type Error = u8;
+        fn send(&mut self, _frame: Frame) -> Result<(), Self::Error> {
+            self.sent += 1;
+            if self.sent <= self.failures {
+                Err(self.sent)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn retry() {
+        let frame = crate::command::reset().into_command().into();
+        let mut delays = 0;
+        let flaky = Flaky {
+            failures: 2,
+            sent: 0,
+        };
+        let mut retry = Retry::new(flaky, 2, || delays += 1);
+        let res = retry.send(frame);
+        assert!(res == Ok(()), "Got {:?}, expected Ok(())", res);
+        let sent = retry.release().sent;
+        assert!(sent == 3, "Got {} attempts, expected 3", sent);
+        assert!(delays == 2, "Got {} delays, expected 2", delays);
+        let flaky = Flaky {
+            failures: 5,
+            sent: 0,
+        };
+        let mut retry = Retry::new(flaky, 2, || {});
+        let res = retry.send(frame);
+        assert!(res == Err(3), "Got {:?}, expected Err(3)", res);
+    }
+}
@@ -0,0 +1,187 @@
+use super::{Frame, WriteFrame};
+use crate::command::headphone_out::HpVoldB;
+use crate::command::line_in::InVoldB;
+use crate::decode::{self, *};
+use crate::registers;
+use core::convert::Infallible;
+
+///Interface emulating the codec register file, for host simulations.
+///
+///Every written frame is applied to the register it addresses, like the codec does. Writing the
+///reset register restores reset values, and the `inboth`/`hpboth` bits also load the other
+///channel. Getters decode the stored registers.
+///
+///## Example
+///```
+///# use wm8731_alt::prelude::*;
+///# use wm8731_alt::Wm8731;
+///# use wm8731_alt::interface::SimulatedCodec;
+///let mut wm8731 = Wm8731::new(SimulatedCodec::new()).unwrap();
+///wm8731.send(both_headphone_out().hpvol().db(HpVoldB::N6DB)).unwrap();
+///let codec = wm8731.release();
+///assert_eq!(codec.headphone_volume(), [HpVoldB::N6DB, HpVoldB::N6DB]);
+///```
+#[derive(Debug, Clone)]
+pub struct SimulatedCodec {
+    registers: [u16; 16],
+}
+
+impl Default for SimulatedCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulatedCodec {
+    ///Instantiate a codec in its reset state.
+    pub fn new() -> Self {
+        let mut registers = [0; 16];
+        registers[..10].copy_from_slice(&registers::DEFAULTS);
+        Self { registers }
+    }
+    ///Return the 9 bits content of a register, `0` for unknown addresses.
+    pub fn register(&self, addr: u8) -> u16 {
+        self.registers.get(addr as usize).copied().unwrap_or(0)
+    }
+    ///Return the content of a register, decoded.
+    pub fn decoded(&self, addr: u8) -> DecodedRegister {
+        decode::decode((addr as u16) << 9 | self.register(addr))
+    }
+    pub fn left_line_in(&self) -> LineInFields {
+        decode::line_in_fields(self.register(registers::LEFT_LINE_IN))
+    }
+    pub fn right_line_in(&self) -> LineInFields {
+        decode::line_in_fields(self.register(registers::RIGHT_LINE_IN))
+    }
+    pub fn left_headphone_out(&self) -> HeadphoneOutFields {
+        decode::headphone_out_fields(self.register(registers::LEFT_HEADPHONE_OUT))
+    }
+    pub fn right_headphone_out(&self) -> HeadphoneOutFields {
+        decode::headphone_out_fields(self.register(registers::RIGHT_HEADPHONE_OUT))
+    }
+    pub fn analogue_audio_path(&self) -> AnalogueAudioPathFields {
+        decode::analogue_audio_path_fields(self.register(registers::ANALOGUE_AUDIO_PATH))
+    }
+    pub fn digital_audio_path(&self) -> DigitalAudioPathFields {
+        decode::digital_audio_path_fields(self.register(registers::DIGITAL_AUDIO_PATH))
+    }
+    pub fn power_down(&self) -> PowerDownFields {
+        decode::power_down_fields(self.register(registers::POWER_DOWN))
+    }
+    pub fn digital_audio_interface(&self) -> DigitalAudioInterfaceFields {
+        decode::digital_audio_interface_fields(self.register(registers::DIGITAL_AUDIO_INTERFACE))
+    }
+    pub fn sampling(&self) -> SamplingFields {
+        decode::sampling_fields(self.register(registers::SAMPLING))
+    }
+    ///Return `true` if the digital audio interface is activated.
+    pub fn is_active(&self) -> bool {
+        decode::active_control_fields(self.register(registers::ACTIVE_CONTROL)).active
+    }
+    ///Return left and right line input volumes.
+    pub fn line_in_volume(&self) -> [InVoldB; 2] {
+        [
+            InVoldB::from_raw(self.left_line_in().invol),
+            InVoldB::from_raw(self.right_line_in().invol),
+        ]
+    }
+    ///Return left and right headphone output volumes.
+    pub fn headphone_volume(&self) -> [HpVoldB; 2] {
+        [
+            HpVoldB::from_raw(self.left_headphone_out().hpvol),
+            HpVoldB::from_raw(self.right_headphone_out().hpvol),
+        ]
+    }
+}
+
+impl WriteFrame for SimulatedCodec {
+    type Error = Infallible;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        let word: u16 = frame.into();
+        let addr = (word >> 9) as u8;
+        let value = word & 0x1FF;
+        if addr == registers::RESET {
+            *self = Self::new();
+            return Ok(());
+        }
+        //like the codec, ignore writes to unknown registers
+        if let Some(reg) = self.registers.get_mut(addr as usize) {
+            *reg = value;
+        }
+        if let Some(other) = registers::both_target(addr, value) {
+            let reg = &mut self.registers[other as usize];
+            *reg = *reg & registers::BOTH | value & !registers::BOTH;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    #[test]
+    fn simulated() {
+        let mut codec = SimulatedCodec::new();
+        let frames: [Frame; 3] = [
+            both_line_in().invol().db(InVoldB::P0DB).into(),
+            power_down().none().into(),
+            active_control().active().into(),
+        ];
+        for &frame in frames.iter() {
+            codec.send(frame).unwrap();
+        }
+        let test = codec.line_in_volume();
+        let expected = [InVoldB::P0DB; 2];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = codec.power_down().poweroff;
+        assert!(!test, "Got {}, expected false", test);
+        assert!(codec.is_active(), "Got inactive, expected active");
+        codec.send(reset().into()).unwrap();
+        let test = codec.register(registers::POWER_DOWN);
+        let expected = registers::POWER_DOWN_DEFAULT;
+        assert!(
+            test == expected,
+            "Got {:#b}, expected {:#b}",
+            test,
+            expected
+        );
+    }
+    #[test]
+    fn both_bit_keeps_target_both() {
+        let mut codec = SimulatedCodec::new();
+        codec
+            .send(right_line_in().inboth().enable().into())
+            .unwrap();
+        codec
+            .send(both_line_in().invol().db(InVoldB::P0DB).into())
+            .unwrap();
+        let test = codec.register(registers::RIGHT_LINE_IN);
+        let expected = registers::BOTH | 0b1001_0111;
+        assert!(
+            test == expected,
+            "Got {:#b}, expected {:#b}",
+            test,
+            expected
+        );
+        codec
+            .send(left_headphone_out().hpboth().enable().into())
+            .unwrap();
+        let test = codec.register(registers::RIGHT_HEADPHONE_OUT);
+        let expected = registers::HEADPHONE_OUT_DEFAULT;
+        assert!(
+            test == expected,
+            "Got {:#b}, expected {:#b}",
+            test,
+            expected
+        );
+    }
+    #[test]
+    fn unknown_address() {
+        let mut codec = SimulatedCodec::new();
+        let frame = unsafe { Command::from_raw(0x1F << 9) }.into();
+        codec.send(frame).unwrap();
+        let test = codec.register(0x1F);
+        assert!(test == 0, "Got {:#b}, expected {:#b}", test, 0);
+    }
+}
@@ -0,0 +1,76 @@
+use super::{Frame, WriteFrame};
+#[cfg(any(test, feature = "std"))]
+use std::vec::Vec;
+
+///Interface wrapper handing every frame to a closure before forwarding it to the inner interface,
+///for example to capture the exact command stream in a golden test.
+///
+///## Example
+///```
+///# use wm8731_alt::interface::{NullInterface, TeeInterface};
+///# use wm8731_alt::Wm8731;
+///let mut count = 0;
+///let interface = TeeInterface::new(NullInterface, |_frame| count += 1);
+///let wm8731 = Wm8731::new(interface);
+///```
+pub struct TeeInterface<W, F> {
+    inner: W,
+    log: F,
+}
+
+impl<W, F> TeeInterface<W, F>
+where
+    W: WriteFrame,
+    F: FnMut(Frame),
+{
+    pub fn new(inner: W, log: F) -> Self {
+        Self { inner, log }
+    }
+    ///Release the wrapped interface.
+    pub fn release(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl<'a, W> TeeInterface<W, fn(Frame)>
+where
+    W: WriteFrame,
+{
+    ///Push every frame, as a raw control word, into `log`.
+    pub fn with_vec(inner: W, log: &'a mut Vec<u16>) -> TeeInterface<W, impl FnMut(Frame) + 'a> {
+        TeeInterface::new(inner, move |frame| log.push(frame.into()))
+    }
+}
+
+impl<W, F> WriteFrame for TeeInterface<W, F>
+where
+    W: WriteFrame,
+    F: FnMut(Frame),
+{
+    type Error = W::Error;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        (self.log)(frame);
+        self.inner.send(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    use crate::interface::NullInterface;
+
+    #[test]
+    fn tee() {
+        let mut log = Vec::new();
+        let mut tee = TeeInterface::with_vec(NullInterface, &mut log);
+        let frame = reset().into_command().into();
+        tee.send(frame).unwrap();
+        tee.send(active_control().active().into_command().into())
+            .unwrap();
+        drop(tee);
+        let expected = [0b1111 << 9, 0b1001 << 9 | 0b1];
+        assert!(log == expected, "Got {:?}, expected {:?}", log, expected);
+    }
+}
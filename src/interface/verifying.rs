@@ -0,0 +1,126 @@
+use super::{Frame, WriteFrame};
+
+///First difference between sent frames and the expected sequence.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Mismatch {
+    ///Position of the frame in the sequence.
+    pub index: usize,
+    ///Expected control word, `None` if the sequence was already complete.
+    pub expected: Option<u16>,
+    ///Sent control word.
+    pub sent: u16,
+    ///Value of the targeted register before this write, `None` if it wasn't written yet.
+    pub previous: Option<u16>,
+}
+
+///Interface wrapper checking sent frames against an expected sequence, for catching
+///initialization order bugs with a mock bus.
+///
+///It keeps a shadow copy of every register it sends, and records the first frame that differs
+///from the expected sequence. Frames are still forwarded to the inner interface.
+///
+///## Example
+///```
+///# use wm8731_alt::prelude::*;
+///# use wm8731_alt::Wm8731;
+///# use wm8731_alt::interface::{NullInterface, VerifyingInterface};
+///let expected = [reset().into_command().into_raw()];
+///let wm8731 = Wm8731::new(VerifyingInterface::new(NullInterface, &expected)).unwrap();
+///let interface = wm8731.release();
+///assert!(interface.mismatch().is_none() && interface.is_complete());
+///```
+pub struct VerifyingInterface<'a, W> {
+    inner: W,
+    expected: &'a [u16],
+    index: usize,
+    shadow: [Option<u16>; 16],
+    mismatch: Option<Mismatch>,
+}
+
+impl<'a, W> VerifyingInterface<'a, W>
+where
+    W: WriteFrame,
+{
+    pub fn new(inner: W, expected: &'a [u16]) -> Self {
+        Self {
+            inner,
+            expected,
+            index: 0,
+            shadow: [None; 16],
+            mismatch: None,
+        }
+    }
+    ///Return the first difference with the expected sequence, if any.
+    pub fn mismatch(&self) -> Option<Mismatch> {
+        self.mismatch
+    }
+    ///Return `true` if every expected frame was sent.
+    pub fn is_complete(&self) -> bool {
+        self.index >= self.expected.len()
+    }
+    ///Return the last value written to a register, or `None` if it wasn't written.
+    pub fn register(&self, addr: u8) -> Option<u16> {
+        self.shadow.get(addr as usize).copied().flatten()
+    }
+    ///Release the wrapped interface.
+    pub fn release(self) -> W {
+        self.inner
+    }
+}
+
+impl<'a, W> WriteFrame for VerifyingInterface<'a, W>
+where
+    W: WriteFrame,
+{
+    type Error = W::Error;
+    fn send(&mut self, frame: Frame) -> Result<(), Self::Error> {
+        self.inner.send(frame)?;
+        let word: u16 = frame.into();
+        let addr = (word >> 9) as usize;
+        let expected = self.expected.get(self.index).copied();
+        if self.mismatch.is_none() && expected != Some(word) {
+            self.mismatch = Some(Mismatch {
+                index: self.index,
+                expected,
+                sent: word,
+                previous: self.shadow.get(addr).copied().flatten(),
+            });
+        }
+        if let Some(reg) = self.shadow.get_mut(addr) {
+            *reg = Some(word & 0x1FF);
+        }
+        self.index += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    use crate::interface::NullInterface;
+
+    #[test]
+    fn verifying() {
+        let expected = [
+            active_control().into_command().into_raw(),
+            active_control().active().into_command().into_raw(),
+        ];
+        let mut verif = VerifyingInterface::new(NullInterface, &expected);
+        let frame = active_control().active().into_command().into();
+        verif.send(frame).unwrap();
+        verif.send(frame).unwrap();
+        let test = verif.mismatch();
+        let expected = Some(Mismatch {
+            index: 0,
+            expected: Some(0b1001 << 9),
+            sent: 0b1001 << 9 | 0b1,
+            previous: None,
+        });
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        assert!(verif.is_complete());
+        let reg = verif.register(9);
+        assert!(reg == Some(0b1), "Got {:?}, expected Some(1)", reg);
+    }
+}
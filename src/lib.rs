@@ -10,9 +10,9 @@
 //! # #[cfg(any())]
 //! # {
 //! // first, you need to instantiate an interface
-//! let interface = I2CInterface::new(i2c, 0b0011010);
+//! let interface = I2CInterface::with_address(i2c, Wm8731Address::Csb0);
 //! // then, you instantiate the driver using this interface
-//! let wm8731 = Wm8731::new(interface);
+//! let mut wm8731 = Wm8731::new(interface).unwrap();
 //! // finally, you build a command and send it.
 //! let cmd = left_headphone_out()
 //!     .hpvol()
@@ -20,24 +20,97 @@
 //!     .hpboth()
 //!     .set_bit()
 //!     .into_command();
-//! wm8731.send(cmd);
+//! wm8731.send(cmd).unwrap();
 //! # }
 //! ```
 //!
+//! ## Panics
+//!
+//! Public APIs don't panic, fallible conversions return a `Result` instead, see for example
+//! `try_bits` or `from_scaled`. The only exceptions are debug assertions of `unsafe` constructors
+//! taking raw values, like [`Frame::from_parts`](interface::Frame::from_parts), they're compiled
+//! out in release builds.
+//!
 #![no_std]
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+use crate::command::headphone_out::HpVoldB;
 use crate::command::Command;
-use crate::interface::WriteFrame;
+#[cfg(feature = "async")]
+use crate::interface::AsyncWriteFrame;
+use crate::interface::{Frame, WriteFrame};
+use embedded_hal::blocking::delay::DelayUs;
 
 #[macro_use]
 mod macros;
 
 pub mod command;
+pub mod config;
+pub mod decode;
 pub mod interface;
 pub mod prelude;
+pub mod queue;
+pub mod registers;
+pub mod sequence;
+#[cfg(test)]
+mod test_utils;
+
+///Register content after a reset, indexed by register address.
+fn reset_registers() -> [Option<u16>; 10] {
+    registers::DEFAULTS.map(Some)
+}
+
+///Update shadow registers after a frame was sent.
+///
+///A write with the BOTH bit set also loads the other channel, whose BOTH bit is kept. When that
+///bit is unknown, the other channel stays unknown.
+fn update_shadow(shadow: &mut [Option<u16>; 10], frame: Frame) {
+    let word: u16 = frame.into();
+    let addr = (word >> 9) as u8;
+    let value = word & 0x1FF;
+    if let Some(reg) = shadow.get_mut(addr as usize) {
+        *reg = Some(value);
+    } else if addr == registers::RESET {
+        *shadow = reset_registers();
+    }
+    if let Some(other) = registers::both_target(addr, value) {
+        let reg = &mut shadow[other as usize];
+        *reg = reg.map(|old| old & registers::BOTH | value & !registers::BOTH);
+    }
+}
+
+///Fields changed by [`Wm8731::mute`], to restore them on [`Wm8731::unmute`].
+#[derive(Debug, Copy, Clone)]
+struct MuteState {
+    ///INMUTE of left and right line inputs.
+    inmute: [bool; 2],
+    ///DACMU.
+    dacmu: bool,
+    ///`true` if outputs were powered down by the mute.
+    outpd: bool,
+}
 
 ///The wm8731 driver
+///
+///The codec can't be read, so the driver keeps a shadow copy of every register it writes.
 pub struct Wm8731<I> {
     interface: I,
+    shadow: [Option<u16>; 10],
+    mute_state: Option<MuteState>,
+}
+
+impl<I> Wm8731<I> {
+    ///Release the interface, consuming the driver.
+    pub fn release(self) -> I {
+        self.interface
+    }
+
+    ///Give access to the underlying interface.
+    ///
+    ///Frames sent directly through the interface aren't tracked by the shadow registers.
+    pub fn interface_mut(&mut self) -> &mut I {
+        &mut self.interface
+    }
 }
 
 impl<I> Wm8731<I>
@@ -45,23 +118,428 @@ where
     I: WriteFrame,
 {
     ///Instantiate a driver. This also reset the codec to guarantee a known state.
-    pub fn new(interface: I) -> Self {
+    ///
+    ///Fails if the reset command can't be sent.
+    pub fn new(interface: I) -> Result<Self, I::Error> {
+        use crate::command::reset::*;
+        let mut codec = Self {
+            interface,
+            shadow: [None; 10],
+            mute_state: None,
+        };
+        codec.send(reset().into_command())?;
+        Ok(codec)
+    }
+
+    ///Instantiate a driver without resetting the codec, no I/O is performed.
+    ///
+    ///Useful when the codec is already configured, for example on a warm boot. Since its state is
+    ///unknown, every shadow register is `None` until written.
+    pub fn new_without_reset(interface: I) -> Self {
+        Self {
+            interface,
+            shadow: [None; 10],
+            mute_state: None,
+        }
+    }
+
+    ///Send a command to the codec.
+    ///
+    ///On success, the shadow copy of the targeted register is updated.
+    ///
+    ///Builders in a buildable state can be sent directly, without calling `into_command`.
+    pub fn send<F: Into<Frame>>(&mut self, cmd: F) -> Result<(), I::Error> {
+        self.send_frame(cmd.into())
+    }
+
+    ///Send a command only if it changes the shadow copy of the targeted register.
+    ///
+    ///Return `true` if the command was actually sent. It's always sent when the register content
+    ///is unknown, and reset commands are always sent. A command with the BOTH bit set is also sent
+    ///when it changes the other channel.
+    pub fn send_if_changed<F: Into<Frame>>(&mut self, cmd: F) -> Result<bool, I::Error> {
+        let frame = cmd.into();
+        let word: u16 = frame.into();
+        let (addr, value) = ((word >> 9) as u8, word & 0x1FF);
+        let other_unchanged = match registers::both_target(addr, value) {
+            Some(other) => self
+                .register(other)
+                .is_some_and(|reg| reg & !registers::BOTH == value & !registers::BOTH),
+            None => true,
+        };
+        if self.register(addr) == Some(value) && other_unchanged {
+            return Ok(false);
+        }
+        self.send_frame(frame)?;
+        Ok(true)
+    }
+
+    ///Send several frames in a row, stopping at the first error.
+    ///
+    ///## Example
+    ///```
+    ///# use wm8731_alt::prelude::*;
+    ///# use wm8731_alt::Wm8731;
+    ///# use wm8731_alt::interface::WriteFrame;
+    ///# fn init<I: WriteFrame>(wm8731: &mut Wm8731<I>) -> Result<(), I::Error> {
+    ///wm8731.send_all(
+    ///    [
+    ///        power_down().poweroff().disable().into_command(),
+    ///        active_control().active().into_command(),
+    ///    ]
+    ///    .iter()
+    ///    .map(|&cmd| cmd.into()),
+    ///)
+    ///# }
+    ///```
+    pub fn send_all<F>(&mut self, frames: F) -> Result<(), I::Error>
+    where
+        F: IntoIterator<Item = Frame>,
+    {
+        for frame in frames {
+            self.send_frame(frame)?;
+        }
+        Ok(())
+    }
+
+    ///Send the default initialization sequence.
+    ///
+    ///See [`sequence::init_default`] for the resulting configuration and the written registers.
+    pub fn init_default(&mut self) -> Result<(), I::Error> {
+        self.send_all(sequence::init_default())
+    }
+
+    ///Apply a whole configuration.
+    ///
+    ///See [`Wm8731Config::to_commands`](config::Wm8731Config::to_commands) for the written
+    ///registers. Nothing is sent if the configuration isn't supported by the codec.
+    pub fn apply<MCLK>(
+        &mut self,
+        config: &config::Wm8731Config<MCLK>,
+    ) -> Result<(), config::ApplyError<I::Error>> {
+        let frames = config.to_commands()?;
+        self.send_all(frames).map_err(config::ApplyError::Interface)
+    }
+
+    ///Send the anti-pop power up sequence.
+    ///
+    ///See [`sequence::power_up`] for the written registers, the digital audio path is built from
+    ///the shadow register.
+    pub fn power_up_sequence(&mut self) -> Result<(), I::Error> {
+        self.send_all(sequence::power_up(self.current_digital_audio_path()))
+    }
+
+    ///Send the anti-pop power up sequence, waiting for the VMID reference to charge before
+    ///enabling outputs.
+    ///
+    ///Steps are the ones of [`sequence::timed_power_up`]. Outputs are powered up only once VMID
+    ///is stable and the DAC unmuted, which avoids the pop heard on naive power up. The digital
+    ///audio path is built from the shadow register.
+    pub fn anti_pop_startup<D>(&mut self, delay: &mut D) -> Result<(), I::Error>
+    where
+        D: DelayUs<u32>,
+    {
+        let steps = sequence::timed_power_up(self.current_digital_audio_path());
+        self.send_sequenced(&steps, delay)
+    }
+
+    ///Digital audio path builder loaded with the shadow register.
+    fn current_digital_audio_path(&self) -> command::digital_audio_path::DigitalAudioPath {
+        let current = self.current(registers::DIGITAL_AUDIO_PATH);
+        command::digital_audio_path::DigitalAudioPath::from_command(current)
+    }
+
+    ///Send frames interleaved with delays, stopping at the first error.
+    ///
+    ///## Example
+    ///```
+    ///# use wm8731_alt::prelude::*;
+    ///# use wm8731_alt::Wm8731;
+    ///# use wm8731_alt::interface::WriteFrame;
+    ///# use wm8731_alt::sequence::Step;
+    ///# use embedded_hal::blocking::delay::DelayUs;
+    ///# fn init<I: WriteFrame, D: DelayUs<u32>>(
+    ///#     wm8731: &mut Wm8731<I>,
+    ///#     delay: &mut D,
+    ///# ) -> Result<(), I::Error> {
+    ///let steps = [
+    ///    Step::Cmd(power_down().poweroff().disable().into_command().into()),
+    ///    Step::Delay(1_000),
+    ///    Step::Cmd(active_control().active().into_command().into()),
+    ///];
+    ///wm8731.send_sequenced(&steps, delay)
+    ///# }
+    ///```
+    pub fn send_sequenced<D>(
+        &mut self,
+        steps: &[sequence::Step],
+        delay: &mut D,
+    ) -> Result<(), I::Error>
+    where
+        D: DelayUs<u32>,
+    {
+        for step in steps {
+            match *step {
+                sequence::Step::Cmd(frame) => self.send_frame(frame)?,
+                sequence::Step::Delay(us) => delay.delay_us(us),
+            }
+        }
+        Ok(())
+    }
+
+    ///Walk both headphone outputs volume from `from` to `to` in 1dB steps, waiting `step_us`
+    ///microseconds between steps, to avoid clicks. Works for fade in and fade out.
+    ///
+    ///Each step is a single command using the `hpboth` bit, `from` being written first. Other
+    ///fields are kept from the shadow register of the left output.
+    pub fn fade_headphone<D>(
+        &mut self,
+        from: HpVoldB,
+        to: HpVoldB,
+        delay: &mut D,
+        step_us: u32,
+    ) -> Result<(), I::Error>
+    where
+        D: DelayUs<u32>,
+    {
+        use crate::command::headphone_out::BothHeadphoneOut;
+        let base = BothHeadphoneOut::from_command(self.current(registers::LEFT_HEADPHONE_OUT));
+        let mut volume = from;
+        self.send(base.hpvol().db(volume))?;
+        while volume != to {
+            let next = if volume.into_raw() < to.into_raw() {
+                volume.step_up()
+            } else {
+                volume.step_down()
+            };
+            volume = match next {
+                Some(next) => next,
+                None => break,
+            };
+            delay.delay_us(step_us);
+            self.send(base.hpvol().db(volume))?;
+        }
+        Ok(())
+    }
+
+    fn send_frame(&mut self, frame: Frame) -> Result<(), I::Error> {
+        self.interface.send(frame)?;
+        update_shadow(&mut self.shadow, frame);
+        if u16::from(frame) >> 9 == registers::RESET as u16 {
+            self.mute_state = None;
+        }
+        Ok(())
+    }
+
+    ///Return the last value written to a register, or `None` if it's unknown.
+    ///
+    ///The value is the 9 bits register content, without the address.
+    pub fn register(&self, addr: u8) -> Option<u16> {
+        self.shadow.get(addr as usize).copied().flatten()
+    }
+
+    ///Write every register, in address order from 0 to 9, stopping at the first error.
+    ///
+    ///Values are 9 bits register contents without address, like returned by
+    ///[`register`](Self::register), upper bits are ignored. This allows to restore a previously
+    ///saved state.
+    pub fn write_registers(&mut self, regs: &[u16; 10]) -> Result<(), I::Error> {
+        for (addr, &value) in regs.iter().enumerate() {
+            let cmd = unsafe { Command::from_raw((addr as u16) << 9 | value & 0x1FF) };
+            self.send(cmd)?;
+        }
+        Ok(())
+    }
+
+    ///Return `true` if the digital audio interface was activated.
+    pub fn is_active(&self) -> bool {
+        matches!(self.register(registers::ACTIVE_CONTROL), Some(data) if data & 0b1 != 0)
+    }
+
+    ///Mute the DAC then both line inputs.
+    ///
+    ///The DAC is muted first to avoid pop. Other fields, volumes included, are kept from the shadow
+    ///registers, or from the reset values when unknown. The mute state before the first call is
+    ///saved, so [`unmute`](Self::unmute) restores it.
+    pub fn mute(&mut self) -> Result<(), I::Error> {
+        use crate::command::digital_audio_path::DigitalAudioPath;
+        use crate::command::line_in::{LeftLineIn, RightLineIn};
+        let dac = self.current(registers::DIGITAL_AUDIO_PATH);
+        let left = self.current(registers::LEFT_LINE_IN);
+        let right = self.current(registers::RIGHT_LINE_IN);
+        if self.mute_state.is_none() {
+            self.mute_state = Some(MuteState {
+                inmute: [left.value() & 1 << 7 != 0, right.value() & 1 << 7 != 0],
+                dacmu: dac.value() & 1 << 3 != 0,
+                outpd: false,
+            });
+        }
+        let dac = DigitalAudioPath::from_command(dac);
+        let left = LeftLineIn::from_command(left);
+        let right = RightLineIn::from_command(right);
+        self.send(dac.dacmu().enable().into_command())?;
+        self.send(left.inmute().enable().inboth().disable().into_command())?;
+        self.send(right.inmute().enable().inboth().disable().into_command())
+    }
+
+    ///Like [`mute`](Self::mute), then power down outputs.
+    ///
+    ///[`unmute`](Self::unmute) only powers outputs up again if they were powered up before.
+    pub fn mute_and_power_down_outputs(&mut self) -> Result<(), I::Error> {
+        use crate::command::power_down::PowerDown;
+        self.mute()?;
+        let power = self.current(registers::POWER_DOWN);
+        if power.value() & 1 << 4 == 0 {
+            if let Some(state) = self.mute_state.as_mut() {
+                state.outpd = true;
+            }
+        }
+        let power = PowerDown::from_command(power);
+        self.send(power.outpd().enable().into_command())
+    }
+
+    ///Restore the line inputs, then the DAC, to their state before [`mute`](Self::mute), and
+    ///finally power up outputs if they were powered down by
+    ///[`mute_and_power_down_outputs`](Self::mute_and_power_down_outputs).
+    ///
+    ///Outputs are powered up last to avoid pop. Nothing is sent if the codec wasn't muted with
+    ///`mute`, or was reset since.
+    pub fn unmute(&mut self) -> Result<(), I::Error> {
+        use crate::command::digital_audio_path::DigitalAudioPath;
+        use crate::command::line_in::{LeftLineIn, RightLineIn};
+        use crate::command::power_down::PowerDown;
+        let state = match self.mute_state {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+        let left = LeftLineIn::from_command(self.current(registers::LEFT_LINE_IN));
+        let right = RightLineIn::from_command(self.current(registers::RIGHT_LINE_IN));
+        let dac = DigitalAudioPath::from_command(self.current(registers::DIGITAL_AUDIO_PATH));
+        self.send(left.inmute().bit(state.inmute[0]).inboth().disable())?;
+        self.send(right.inmute().bit(state.inmute[1]).inboth().disable())?;
+        self.send(dac.dacmu().bit(state.dacmu))?;
+        if state.outpd {
+            let power = PowerDown::from_command(self.current(registers::POWER_DOWN));
+            self.send(power.outpd().disable())?;
+        }
+        self.mute_state = None;
+        Ok(())
+    }
+
+    ///Mute or unmute the DAC, other fields of the digital audio path are kept from the shadow
+    ///register.
+    ///
+    ///Volumes aren't touched, so unmuting restores the previous level. To avoid pops when
+    ///outputs are also switched, mute the DAC before powering down outputs, and power up outputs
+    ///after unmuting the DAC, see
+    ///[`set_dac_mute_with_outputs`](Self::set_dac_mute_with_outputs).
+    pub fn set_dac_mute(&mut self, mute: bool) -> Result<(), I::Error> {
+        use crate::command::digital_audio_path::DigitalAudioPath;
+        let dac = DigitalAudioPath::from_command(self.current(registers::DIGITAL_AUDIO_PATH));
+        self.send(dac.dacmu().bit(mute))
+    }
+
+    ///Like [`set_dac_mute`](Self::set_dac_mute), also powering outputs down on mute and up on
+    ///unmute, in a pop free order.
+    ///
+    ///On mute, the DAC is muted then outputs are powered down. On unmute, the DAC is unmuted then
+    ///outputs are powered up.
+    pub fn set_dac_mute_with_outputs(&mut self, mute: bool) -> Result<(), I::Error> {
+        use crate::command::power_down::PowerDown;
+        self.set_dac_mute(mute)?;
+        let power = PowerDown::from_command(self.current(registers::POWER_DOWN));
+        self.send(power.outpd().bit(mute))
+    }
+
+    ///Send the pop free shutdown sequence of
+    ///[`PowerDown::safe_shutdown`](crate::command::power_down::PowerDown::safe_shutdown), built
+    ///from the shadow registers.
+    pub fn safe_shutdown(&mut self) -> Result<(), I::Error> {
+        use crate::command::digital_audio_path::DigitalAudioPath;
+        use crate::command::power_down::PowerDown;
+        let path = DigitalAudioPath::from_command(self.current(registers::DIGITAL_AUDIO_PATH));
+        let power = PowerDown::from_command(self.current(registers::POWER_DOWN));
+        self.send_all(PowerDown::safe_shutdown(path, power))
+    }
+
+    ///Return the shadow register as a command, or the reset value if it's unknown.
+    fn current(&self, addr: u8) -> Command<()> {
+        let value = self
+            .register(addr)
+            .or_else(|| reset_registers().get(addr as usize).copied().flatten())
+            .unwrap_or(0);
+        unsafe { Command::from_raw((addr as u16) << 9 | value) }
+    }
+}
+
+///The wm8731 driver, using an asynchronous interface.
+///
+///Like [`Wm8731`], it keeps a shadow copy of every register it writes.
+#[cfg(feature = "async")]
+pub struct Wm8731Async<I> {
+    interface: I,
+    shadow: [Option<u16>; 10],
+}
+
+#[cfg(feature = "async")]
+impl<I> Wm8731Async<I> {
+    ///Release the interface, consuming the driver.
+    pub fn release(self) -> I {
+        self.interface
+    }
+
+    ///Return the last value written to a register, or `None` if it's unknown.
+    ///
+    ///The value is the 9 bits register content, without the address.
+    pub fn register(&self, addr: u8) -> Option<u16> {
+        self.shadow.get(addr as usize).copied().flatten()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<I> Wm8731Async<I>
+where
+    I: AsyncWriteFrame,
+{
+    ///Instantiate a driver. This also reset the codec to guarantee a known state.
+    ///
+    ///Fails if the reset command can't be sent.
+    pub async fn new(interface: I) -> Result<Self, I::Error> {
         use crate::command::reset::*;
-        let mut codec = Self { interface };
-        codec.send(reset().into_command());
-        codec
+        let mut codec = Self {
+            interface,
+            shadow: [None; 10],
+        };
+        codec.send(reset().into_command()).await?;
+        Ok(codec)
+    }
+
+    ///Instantiate a driver without resetting the codec, no I/O is performed.
+    ///
+    ///Every shadow register is `None` until written.
+    pub fn new_without_reset(interface: I) -> Self {
+        Self {
+            interface,
+            shadow: [None; 10],
+        }
     }
 
     ///Send a command to the codec.
-    pub fn send<T>(&mut self, cmd: Command<T>) {
-        self.interface.send(cmd.into());
+    ///
+    ///On success, the shadow copy of the targeted register is updated.
+    pub async fn send<F: Into<Frame>>(&mut self, cmd: F) -> Result<(), I::Error> {
+        let frame = cmd.into();
+        self.interface.send(frame).await?;
+        update_shadow(&mut self.shadow, frame);
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use embedded_hal::blocking::spi;
+    use crate::test_utils::FakeDelay;
+    use embedded_hal::blocking::{i2c, spi};
     use embedded_hal::digital::v2::OutputPin;
 
     struct FakeSpi;
@@ -78,6 +556,14 @@ mod tests {
         }
     }
 
+    struct BrokenSpi;
+    impl spi::Write<u8> for BrokenSpi {
+        type Error = ();
+        fn write(&mut self, _words: &[u8]) -> Result<(), Self::Error> {
+            Err(())
+        }
+    }
+
     struct FakePin;
 
     impl OutputPin for FakePin {
@@ -95,6 +581,666 @@ mod tests {
         let spi_if: SPIInterface<_, _, u8> = SPIInterface::new(FakeSpi, FakePin);
         let _wm8731 = Wm8731::new(spi_if);
     }
+    #[test]
+    fn send_batch() {
+        use crate::command::*;
+        use crate::interface::{Frame, SPIInterface};
+        use std::vec::Vec;
+        struct RecordSpi(Vec<u16>);
+        impl spi::Write<u16> for RecordSpi {
+            type Error = ();
+            fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+                self.0.extend_from_slice(words);
+                Ok(())
+            }
+        }
+        struct CountPin(std::rc::Rc<core::cell::Cell<usize>>);
+        impl OutputPin for CountPin {
+            type Error = ();
+            fn set_low(&mut self) -> Result<(), Self::Error> {
+                self.0.set(self.0.get() + 1);
+                Ok(())
+            }
+            fn set_high(&mut self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+        let frames: [Frame; 2] = [
+            active_control().active().into(),
+            power_down().poweroff().disable().into(),
+        ];
+        let count = std::rc::Rc::new(core::cell::Cell::new(0));
+        let mut spi_if: SPIInterface<_, _, u16> =
+            SPIInterface::new(RecordSpi(Vec::new()), CountPin(count.clone()));
+        spi_if.send_batch(&frames).unwrap();
+        let spi = spi_if.release();
+        let expected = [0b1001 << 9 | 0b1, 0b110 << 9 | 0b0001_1111];
+        assert!(
+            spi.0 == expected,
+            "Got {:?}, expected {:?}",
+            spi.0,
+            expected
+        );
+        assert!(count.get() == 1, "Got {}, expected 1", count.get());
+    }
+    #[test]
+    fn hardware_cs() {
+        use crate::interface::{SPIInterface, SPIInterfaceError};
+        let spi_if: SPIInterface<_, _, u16> = SPIInterface::new_hardware_cs(FakeSpi);
+        let _wm8731 = Wm8731::new(spi_if).unwrap();
+        let spi_if: SPIInterface<_, _, u8> = SPIInterface::new_hardware_cs(BrokenSpi);
+        let res = Wm8731::new(spi_if).err();
+        let expected = Some(SPIInterfaceError::Spi(()));
+        assert!(res == expected, "Got {:?}, expected {:?}", res, expected);
+    }
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_driver() {
+        use crate::command::*;
+        use crate::interface::RecordingInterface;
+        use crate::test_utils::block_on;
+        let mut wm8731 = block_on(Wm8731Async::new(RecordingInterface::new())).unwrap();
+        let reg = wm8731.register(registers::POWER_DOWN);
+        let expected = Some(registers::POWER_DOWN_DEFAULT);
+        assert!(reg == expected, "Got {:?}, expected {:?}", reg, expected);
+        block_on(wm8731.send(active_control().active())).unwrap();
+        let reg = wm8731.register(registers::ACTIVE_CONTROL);
+        assert!(reg == Some(1), "Got {:?}, expected {:?}", reg, Some(1));
+        let test = wm8731.release().frames().to_vec();
+        let expected = [0b1111 << 9, 0b1001 << 9 | 0b1];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let wm8731 = Wm8731Async::new_without_reset(RecordingInterface::new());
+        let reg = wm8731.register(registers::POWER_DOWN);
+        assert!(reg.is_none(), "Got {:?}, expected None", reg);
+    }
+    #[test]
+    fn shadow_both_bit() {
+        use crate::command::*;
+        use crate::interface::SimulatedCodec;
+        let mut wm8731 = Wm8731::new(SimulatedCodec::new()).unwrap();
+        let right = right_headphone_out().hpvol().db(HpVoldB::N6DB);
+        wm8731.send(right).unwrap();
+        wm8731
+            .send(both_headphone_out().hpvol().db(HpVoldB::P0DB))
+            .unwrap();
+        let reg = wm8731.register(registers::RIGHT_HEADPHONE_OUT);
+        let expected = Some(0b0111_1001);
+        assert!(reg == expected, "Got {:?}, expected {:?}", reg, expected);
+        let sent = wm8731.send_if_changed(right).unwrap();
+        assert!(sent, "Got not sent, expected sent");
+        let test = wm8731.release().headphone_volume();
+        let expected = [HpVoldB::P0DB, HpVoldB::N6DB];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        //the other channel stays unknown if its BOTH bit is unknown
+        let mut wm8731 = Wm8731::new_without_reset(SimulatedCodec::new());
+        wm8731.send(left_line_in().inboth().enable()).unwrap();
+        let reg = wm8731.register(registers::RIGHT_LINE_IN);
+        assert!(reg.is_none(), "Got {:?}, expected None", reg);
+    }
+    #[test]
+    fn dedup_last_both_bit() {
+        use crate::command::*;
+        use crate::interface::{dedup_last, Frame};
+        let both: Frame = both_headphone_out().hpvol().db(HpVoldB::N6DB).into();
+        let left: Frame = left_headphone_out().hpvol().db(HpVoldB::P0DB).into();
+        let right: Frame = right_headphone_out().hpvol().db(HpVoldB::P0DB).into();
+        let mut frames = [both, left];
+        let len = dedup_last(&mut frames);
+        let test = &frames[..len];
+        let expected = [both, left];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let mut frames = [both, left, right];
+        let len = dedup_last(&mut frames);
+        let test = &frames[..len];
+        let expected = [left, right];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn spi_error_is_forwarded() {
+        use crate::interface::{SPIInterface, SPIInterfaceError};
+        let spi_if: SPIInterface<_, _, u8> = SPIInterface::new(BrokenSpi, FakePin);
+        let res = Wm8731::new(spi_if).err();
+        let expected = Some(SPIInterfaceError::Spi(()));
+        assert!(res == expected, "Got {:?}, expected {:?}", res, expected);
+    }
+    #[test]
+    fn i2c_address() {
+        use crate::interface::{I2CInterface, Wm8731Address};
+        struct AddrI2c(u8);
+        impl i2c::Write for AddrI2c {
+            type Error = ();
+            fn write(&mut self, address: u8, _bytes: &[u8]) -> Result<(), Self::Error> {
+                self.0 = address;
+                Ok(())
+            }
+        }
+        let i2c_if = I2CInterface::with_address(AddrI2c(0), Wm8731Address::Csb1);
+        let wm8731 = Wm8731::new(i2c_if).unwrap();
+        let test = wm8731.release().release().0;
+        assert!(test == 0x1B, "Got {:#x}, expected {:#x}", test, 0x1B);
+    }
+    #[test]
+    fn byte_order() {
+        use crate::interface::{I2CInterface, LsbFirst, SPIInterfaceU8, WriteFrame};
+        struct BytesI2c([u8; 2]);
+        impl i2c::Write for BytesI2c {
+            type Error = ();
+            fn write(&mut self, _address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+                self.0.copy_from_slice(bytes);
+                Ok(())
+            }
+        }
+        struct BytesSpi([u8; 2]);
+        impl spi::Write<u8> for BytesSpi {
+            type Error = ();
+            fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+                self.0.copy_from_slice(words);
+                Ok(())
+            }
+        }
+        let frame = crate::command::active_control()
+            .active()
+            .into_command()
+            .into();
+        let mut i2c_if = I2CInterface::new(BytesI2c([0; 2]), 0x1A);
+        i2c_if.send(frame).unwrap();
+        let test = i2c_if.release().0;
+        assert!(
+            test == [0x12, 0x01],
+            "Got {:?}, expected {:?}",
+            test,
+            [0x12, 0x01]
+        );
+        let mut i2c_if = I2CInterface::new(BytesI2c([0; 2]), 0x1A).into_byte_order::<LsbFirst>();
+        i2c_if.send(frame).unwrap();
+        let test = i2c_if.release().0;
+        assert!(
+            test == [0x01, 0x12],
+            "Got {:?}, expected {:?}",
+            test,
+            [0x01, 0x12]
+        );
+        let mut spi_if =
+            SPIInterfaceU8::new_hardware_cs(BytesSpi([0; 2])).into_byte_order::<LsbFirst>();
+        spi_if.send(frame).unwrap();
+        let test = spi_if.release().0;
+        assert!(
+            test == [0x01, 0x12],
+            "Got {:?}, expected {:?}",
+            test,
+            [0x01, 0x12]
+        );
+    }
+    #[test]
+    fn send_sequenced() {
+        use crate::command::*;
+        use crate::interface::WriteFrame;
+        use crate::sequence::Step;
+        use core::cell::RefCell;
+        use std::vec::Vec;
+        ///Interface logging frames, failing on frames addressed to `fail_on`.
+        struct LogInterface<'a> {
+            log: &'a RefCell<Vec<Step>>,
+            fail_on: Option<u8>,
+        }
+        impl WriteFrame for LogInterface<'_> {
+            type Error = Frame;
+            fn send(&mut self, frame: Frame) -> Result<(), Frame> {
+                if self.fail_on == Some((u16::from(frame) >> 9) as u8) {
+                    return Err(frame);
+                }
+                self.log.borrow_mut().push(Step::Cmd(frame));
+                Ok(())
+            }
+        }
+        struct LogDelay<'a>(&'a RefCell<Vec<Step>>);
+        impl DelayUs<u32> for LogDelay<'_> {
+            fn delay_us(&mut self, us: u32) {
+                self.0.borrow_mut().push(Step::Delay(us));
+            }
+        }
+        let steps = [
+            Step::Cmd(power_down().poweroff().disable().into()),
+            Step::Delay(10),
+            Step::Cmd(digital_audio_path().dacmu().disable().into()),
+            Step::Delay(20),
+            Step::Cmd(active_control().active().into()),
+        ];
+        let log = RefCell::new(Vec::new());
+        let interface = LogInterface {
+            log: &log,
+            fail_on: None,
+        };
+        let mut wm8731 = Wm8731::new_without_reset(interface);
+        wm8731.send_sequenced(&steps, &mut LogDelay(&log)).unwrap();
+        let test = log.take();
+        assert!(test == steps, "Got {:?}, expected {:?}", test, steps);
+        //stop at the first error
+        let interface = LogInterface {
+            log: &log,
+            fail_on: Some(registers::DIGITAL_AUDIO_PATH),
+        };
+        let mut wm8731 = Wm8731::new_without_reset(interface);
+        let test = wm8731.send_sequenced(&steps, &mut LogDelay(&log));
+        let expected = Err(digital_audio_path().dacmu().disable().into());
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = log.take();
+        assert!(
+            test == steps[..2],
+            "Got {:?}, expected {:?}",
+            test,
+            &steps[..2]
+        );
+        let reg = wm8731.register(registers::DIGITAL_AUDIO_PATH);
+        assert!(reg.is_none(), "Got {:?}, expected None", reg);
+    }
+    #[test]
+    fn anti_pop_startup() {
+        use crate::interface::RecordingInterface;
+        let mut delay = FakeDelay::default();
+        let mut wm8731 = Wm8731::new_without_reset(RecordingInterface::new());
+        let path = crate::command::digital_audio_path().deemp().f48k();
+        wm8731.send(path).unwrap();
+        wm8731.interface_mut().clear();
+        wm8731.anti_pop_startup(&mut delay).unwrap();
+        let test = wm8731.release().frames().to_vec();
+        let expected = sequence::power_up(path).map(u16::from);
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        assert!(
+            delay.delays == [sequence::VMID_SETTLING_US],
+            "Got {:?}, expected {:?}",
+            delay.delays,
+            [sequence::VMID_SETTLING_US]
+        );
+    }
+    #[test]
+    fn any_width() {
+        use crate::command::*;
+        use crate::interface::{SPIInterfaceAnyWidth, SpiWordSize};
+        use std::vec::Vec;
+        #[derive(Default)]
+        struct RecordSpi {
+            bytes: Vec<u8>,
+            words: Vec<u16>,
+        }
+        impl spi::Write<u8> for RecordSpi {
+            type Error = ();
+            fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+                self.bytes.extend_from_slice(words);
+                Ok(())
+            }
+        }
+        impl spi::Write<u16> for RecordSpi {
+            type Error = ();
+            fn write(&mut self, words: &[u16]) -> Result<(), Self::Error> {
+                self.words.extend_from_slice(words);
+                Ok(())
+            }
+        }
+        let spi_if = SPIInterfaceAnyWidth::new(RecordSpi::default(), FakePin, SpiWordSize::U8);
+        let mut wm8731 = Wm8731::new_without_reset(spi_if);
+        wm8731.send(active_control().active()).unwrap();
+        wm8731.interface_mut().set_width(SpiWordSize::U16);
+        wm8731.send(active_control().active()).unwrap();
+        let spi = wm8731.release().release();
+        let expected = [0b0001_0010, 0b0000_0001];
+        assert!(
+            spi.bytes == expected,
+            "Got {:?}, expected {:?}",
+            spi.bytes,
+            expected
+        );
+        let expected = [0b1001 << 9 | 0b1];
+        assert!(
+            spi.words == expected,
+            "Got {:?}, expected {:?}",
+            spi.words,
+            expected
+        );
+    }
+    #[test]
+    fn shared_i2c_bus() {
+        use crate::interface::{I2CInterface, Wm8731Address};
+        use core::cell::RefCell;
+        use std::vec::Vec;
+        struct Bus(Vec<(u8, u8, u8)>);
+        impl i2c::Write for Bus {
+            type Error = ();
+            fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+                self.0.push((address, bytes[0], bytes[1]));
+                Ok(())
+            }
+        }
+        //borrowing proxy, like the ones of bus managers
+        struct Proxy<'a>(&'a RefCell<Bus>);
+        impl i2c::Write for Proxy<'_> {
+            type Error = ();
+            fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+                self.0.borrow_mut().write(address, bytes)
+            }
+        }
+        let bus = RefCell::new(Bus(Vec::new()));
+        let first = Wm8731::new(I2CInterface::with_address(Proxy(&bus), Wm8731Address::Csb0));
+        let second = Wm8731::new(I2CInterface::with_address(Proxy(&bus), Wm8731Address::Csb1));
+        assert!(first.is_ok() && second.is_ok());
+        let test = bus.into_inner().0;
+        let expected = [(0x1A, 0b0001_1110, 0), (0x1B, 0b0001_1110, 0)];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn fade_headphone() {
+        use crate::interface::RecordingInterface;
+        let mut delay = FakeDelay::default();
+        let mut wm8731 = Wm8731::new_without_reset(RecordingInterface::new());
+        wm8731
+            .fade_headphone(HpVoldB::P0DB, HpVoldB::N3DB, &mut delay, 10)
+            .unwrap();
+        let test = wm8731.interface_mut().frames().to_vec();
+        let expected =
+            [0b1111001, 0b1111000, 0b1110111, 0b1110110].map(|vol| 0b10 << 9 | 0b1_0000_0000 | vol);
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        assert!(
+            delay.total() == 30,
+            "Got {}, expected {}",
+            delay.total(),
+            30
+        );
+        wm8731.interface_mut().clear();
+        wm8731
+            .fade_headphone(HpVoldB::N3DB, HpVoldB::N1DB, &mut delay, 10)
+            .unwrap();
+        let test = wm8731.release().frames().to_vec();
+        let expected = [0b1110110, 0b1110111, 0b1111000].map(|vol| 0b10 << 9 | 0b1_0000_0000 | vol);
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn spi_error_display() {
+        use crate::interface::SPIInterfaceError;
+        use std::format;
+        let test = format!("{}", SPIInterfaceError::<u8, ()>::Spi(3));
+        let expected = "bus write error: 3";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+        let test = format!("{}", SPIInterfaceError::<(), u8>::Cs(1));
+        let expected = "CS pin error: 1";
+        assert!(test == expected, "Got {}, expected {}", test, expected);
+    }
+    #[test]
+    fn shadow_registers() {
+        use crate::command::*;
+        use crate::interface::SPIInterface;
+        let spi_if: SPIInterface<_, _, u8> = SPIInterface::new(FakeSpi, FakePin);
+        let mut wm8731 = Wm8731::new(spi_if).unwrap();
+        let reg = wm8731.register(6);
+        let expected = Some(0b1001_1111);
+        assert!(reg == expected, "Got {:?}, expected {:?}", reg, expected);
+        assert!(!wm8731.is_active());
+        wm8731
+            .send(active_control().active().into_command())
+            .unwrap();
+        assert!(wm8731.is_active());
+        let reg = wm8731.register(10);
+        assert!(reg.is_none(), "Got {:?}, expected None", reg);
+    }
+    #[test]
+    fn send_all_update_shadow() {
+        use crate::command::*;
+        use crate::interface::SPIInterface;
+        let spi_if: SPIInterface<_, _, u8> = SPIInterface::new(FakeSpi, FakePin);
+        let mut wm8731 = Wm8731::new(spi_if).unwrap();
+        let cmds = [
+            power_down().poweroff().disable().into_command(),
+            active_control().active().into_command(),
+        ];
+        wm8731.send_all(cmds.iter().map(|&cmd| cmd.into())).unwrap();
+        let reg = wm8731.register(6);
+        let expected = Some(0b0001_1111);
+        assert!(reg == expected, "Got {:?}, expected {:?}", reg, expected);
+        assert!(wm8731.is_active());
+    }
+    #[test]
+    fn send_builder() {
+        use crate::command::sampling::Mclk12M288;
+        use crate::command::*;
+        use crate::interface::RecordingInterface;
+        let mut wm8731 = Wm8731::new_without_reset(RecordingInterface::new());
+        wm8731.send(power_down().outpd().disable()).unwrap();
+        wm8731
+            .send(sampling_with_mclk(Mclk12M288).sample_rate().adc48k_dac48k())
+            .unwrap();
+        wm8731
+            .send(sampling().usb_normal().usb().sr().sr_0b0110())
+            .unwrap();
+        let interface = wm8731.release();
+        let test = interface.frames();
+        let expected = [
+            power_down().outpd().disable().into_command().into_raw(),
+            0b1000 << 9,
+            0b1000 << 9 | 0b1_1001,
+        ];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn set_dac_mute() {
+        use crate::interface::RecordingInterface;
+        let mut wm8731 = Wm8731::new(RecordingInterface::new()).unwrap();
+        wm8731.set_dac_mute(false).unwrap();
+        wm8731.set_dac_mute_with_outputs(true).unwrap();
+        wm8731.set_dac_mute_with_outputs(false).unwrap();
+        let interface = wm8731.release();
+        let test = &interface.frames()[1..];
+        let expected = [
+            0b101 << 9,
+            0b101 << 9 | 0b1000,
+            0b110 << 9 | 0b1001_1111,
+            0b101 << 9,
+            0b110 << 9 | 0b1000_1111,
+        ];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    fn send_if_changed() {
+        use crate::command::*;
+        use crate::interface::RecordingInterface;
+        let mut wm8731 = Wm8731::new_without_reset(RecordingInterface::new());
+        let cmd = active_control().active().into_command();
+        let sent = [
+            wm8731.send_if_changed(cmd).unwrap(),
+            wm8731.send_if_changed(cmd).unwrap(),
+        ];
+        let expected = [true, false];
+        assert!(sent == expected, "Got {:?}, expected {:?}", sent, expected);
+        let sent = wm8731.send_if_changed(reset().into_command()).unwrap();
+        assert!(sent, "Got {:?}, expected {:?}", sent, true);
+        let sent = wm8731
+            .send_if_changed(active_control().into_command())
+            .unwrap();
+        assert!(!sent, "Got {:?}, expected {:?}", sent, false);
+        let len = wm8731.release().frames().len();
+        assert!(len == 2, "Got {}, expected {}", len, 2);
+        //BOTH bit, the other channel changed since
+        let mut wm8731 = Wm8731::new(RecordingInterface::new()).unwrap();
+        wm8731.send(both_line_in().invol().bits(0x10)).unwrap();
+        wm8731.send(right_line_in().invol().bits(0x05)).unwrap();
+        let sent = wm8731
+            .send_if_changed(both_line_in().invol().bits(0x10))
+            .unwrap();
+        assert!(sent, "Got {:?}, expected {:?}", sent, true);
+        let reg = wm8731.register(registers::RIGHT_LINE_IN);
+        let expected = Some(0b0_1001_0000);
+        assert!(reg == expected, "Got {:?}, expected {:?}", reg, expected);
+        let sent = wm8731
+            .send_if_changed(both_line_in().invol().bits(0x10))
+            .unwrap();
+        assert!(!sent, "Got {:?}, expected {:?}", sent, false);
+    }
+    #[test]
+    fn write_registers() {
+        use crate::interface::RecordingInterface;
+        let mut wm8731 = Wm8731::new_without_reset(RecordingInterface::new());
+        let regs = [0, 1, 2, 3, 4, 5, 6, 7, 8, 0xFFFF];
+        wm8731.write_registers(&regs).unwrap();
+        let reg = wm8731.register(9);
+        let expected = Some(0x1FF);
+        assert!(reg == expected, "Got {:?}, expected {:?}", reg, expected);
+        let interface = wm8731.release();
+        let frames = interface.frames();
+        let test = frames[6];
+        let expected = 0b110 << 9 | 6;
+        assert!(
+            test == expected,
+            "Got {:#b}, expected {:#b}",
+            test,
+            expected
+        );
+        let len = frames.len();
+        assert!(len == 10, "Got {}, expected {}", len, 10);
+    }
+    #[test]
+    fn new_without_reset() {
+        use crate::interface::SPIInterface;
+        let spi_if: SPIInterface<_, _, u8> = SPIInterface::new(BrokenSpi, FakePin);
+        let wm8731 = Wm8731::new_without_reset(spi_if);
+        let reg = wm8731.register(6);
+        assert!(reg.is_none(), "Got {:?}, expected None", reg);
+    }
+    #[test]
+    fn release_interface() {
+        use crate::interface::SPIInterface;
+        let spi_if: SPIInterface<_, _, u8> = SPIInterface::new(FakeSpi, FakePin);
+        let mut wm8731 = Wm8731::new(spi_if).unwrap();
+        let _interface: &mut SPIInterface<_, _, u8> = wm8731.interface_mut();
+        let _spi: FakeSpi = wm8731.release().release();
+    }
+    #[test]
+    fn safe_shutdown_from_shadow() {
+        use crate::command::*;
+        use crate::interface::RecordingInterface;
+        let mut wm8731 = Wm8731::new(RecordingInterface::new()).unwrap();
+        wm8731
+            .send(
+                digital_audio_path()
+                    .dacmu()
+                    .disable()
+                    .deemp()
+                    .variant(digital_audio_path::DeempV::F48k),
+            )
+            .unwrap();
+        wm8731
+            .send(power_down().none().micpd().enable().into_command())
+            .unwrap();
+        wm8731.interface_mut().clear();
+        wm8731.safe_shutdown().unwrap();
+        let frames = wm8731.interface_mut().frames();
+        let expected = [
+            0b101 << 9 | 0b0_0000_1110,
+            0b110 << 9 | 0b0_0001_0010,
+            0b110 << 9 | 0b0_0001_1111,
+            0b110 << 9 | 0b0_1111_1111,
+        ];
+        assert!(
+            frames == expected,
+            "Got {:?}, expected {:?}",
+            frames,
+            expected
+        );
+    }
+    #[test]
+    fn mute_unmute() {
+        use crate::command::*;
+        use crate::interface::RecordingInterface;
+        let mut wm8731 = Wm8731::new(RecordingInterface::new()).unwrap();
+        wm8731
+            .send(
+                left_line_in()
+                    .invol()
+                    .db(line_in::InVoldB::N6DB)
+                    .inmute()
+                    .disable()
+                    .into_command(),
+            )
+            .unwrap();
+        wm8731.send(digital_audio_path().dacmu().disable()).unwrap();
+        wm8731
+            .send(power_down().outpd().disable().into_command())
+            .unwrap();
+        wm8731.interface_mut().clear();
+        wm8731.mute_and_power_down_outputs().unwrap();
+        wm8731.unmute().unwrap();
+        //the right line input was muted before, it stays muted
+        let frames = wm8731.interface_mut().frames();
+        let expected = [
+            0b101 << 9 | 0b0_0000_1000,
+            0b0_1001_0011,
+            0b1 << 9 | 0b0_1001_0111,
+            0b110 << 9 | 0b0_1001_1111,
+            0b0_0001_0011,
+            0b1 << 9 | 0b0_1001_0111,
+            0b101 << 9,
+            0b110 << 9 | 0b0_1000_1111,
+        ];
+        assert!(
+            frames == expected,
+            "Got {:?}, expected {:?}",
+            frames,
+            expected
+        );
+        //outputs powered down before the mute stay powered down, the DAC stays muted
+        wm8731
+            .send(power_down().outpd().enable().into_command())
+            .unwrap();
+        wm8731.send(digital_audio_path().dacmu().enable()).unwrap();
+        wm8731.interface_mut().clear();
+        wm8731.mute_and_power_down_outputs().unwrap();
+        wm8731.unmute().unwrap();
+        let frames = &wm8731.interface_mut().frames()[4..];
+        let expected = [
+            0b0_0001_0011,
+            0b1 << 9 | 0b0_1001_0111,
+            0b101 << 9 | 0b0_0000_1000,
+        ];
+        assert!(
+            frames == expected,
+            "Got {:?}, expected {:?}",
+            frames,
+            expected
+        );
+        //unmute without mute does nothing
+        wm8731.interface_mut().clear();
+        wm8731.unmute().unwrap();
+        let frames = wm8731.release();
+        assert!(
+            frames.frames().is_empty(),
+            "Got {:?}, expected no frame",
+            frames.frames()
+        );
+    }
+    #[test]
+    fn null_interface() {
+        use crate::interface::NullInterface;
+        let mut wm8731 = Wm8731::new(NullInterface).unwrap();
+        wm8731.init_default().unwrap();
+        assert!(wm8731.is_active());
+    }
+    #[test]
+    fn init_default_frames() {
+        use crate::interface::RecordingInterface;
+        let mut wm8731 = Wm8731::new(RecordingInterface::new()).unwrap();
+        wm8731.init_default().unwrap();
+        let frames = wm8731.release();
+        let frames = frames.frames();
+        assert!(
+            frames.len() == 10,
+            "Got {} frames, expected 10",
+            frames.len()
+        );
+        let expected = sequence::init_default().map(u16::from);
+        assert!(
+            frames[1..] == expected,
+            "Got {:?}, expected {:?}",
+            &frames[1..],
+            expected
+        );
+    }
     #[cfg(any())]
     fn _should_not_compile() {
         use crate::interface::SPIInterface;
@@ -5,19 +5,39 @@ macro_rules! impl_bits {
         ///
         /// Some bit combinations are invalid, please read the datasheet.
         #[must_use]
-        pub unsafe fn bits(mut self, value: u8) -> $ret {
+        pub const unsafe fn bits(mut self, value: u8) -> $ret {
             let mask = !((!0) << $lenght) << $shift;
             self.cmd.data = self.cmd.data & !mask | (value as u16) << $shift & mask;
             self.cmd
         }
+        ///Writes raw bits to the field, fails if the value doesn't fit in the field.
+        ///
+        /// # Safety
+        ///
+        /// Some bit combinations are invalid, please read the datasheet.
+        pub const unsafe fn try_bits(self, value: u8) -> Result<$ret, $crate::command::OutOfRange> {
+            if (value as u16) >> $lenght != 0 {
+                Err($crate::command::OutOfRange)
+            } else {
+                Ok(self.bits(value))
+            }
+        }
     };
     ($ret:ty, $lenght:literal, $shift:literal) => {
         #[must_use]
-        pub fn bits(mut self, value: u8) -> $ret {
+        pub const fn bits(mut self, value: u8) -> $ret {
             let mask = !((!0) << $lenght) << $shift;
             self.cmd.data = self.cmd.data & !mask | (value as u16) << $shift & mask;
             self.cmd
         }
+        ///Writes raw bits to the field, fails if the value doesn't fit in the field.
+        pub const fn try_bits(self, value: u8) -> Result<$ret, $crate::command::OutOfRange> {
+            if (value as u16) >> $lenght != 0 {
+                Err($crate::command::OutOfRange)
+            } else {
+                Ok(self.bits(value))
+            }
+        }
     };
 }
 
@@ -26,7 +46,7 @@ macro_rules! impl_bit {
     ($ret:ty, $pos:literal) => {
         ///Writes raw bit to the field.
         #[must_use]
-        pub fn bit(mut self, value: bool) -> $ret {
+        pub const fn bit(mut self, value: bool) -> $ret {
             self.cmd.data = self.cmd.data & !(1 << $pos) | (value as u16) << $pos;
             self.cmd
         }
@@ -37,7 +57,7 @@ macro_rules! impl_bit {
 macro_rules! impl_set_bit {
     ($alias:ident, $ret:ty, $pos:literal) => {
         #[must_use]
-        pub fn $alias(mut self) -> $ret {
+        pub const fn $alias(mut self) -> $ret {
             self.cmd.data |= 0b1 << $pos;
             self.cmd
         }
@@ -45,7 +65,7 @@ macro_rules! impl_set_bit {
     ($ret:ty, $pos:literal) => {
         /// Sets the bit field.
         #[must_use]
-        pub fn set_bit(mut self) -> $ret {
+        pub const fn set_bit(mut self) -> $ret {
             self.cmd.data |= 0b1 << $pos;
             self.cmd
         }
@@ -56,7 +76,7 @@ macro_rules! impl_set_bit {
 macro_rules! impl_enable {
     ($ret:ty, $pos:literal) => {
         #[must_use]
-        pub fn enable(mut self) -> $ret {
+        pub const fn enable(mut self) -> $ret {
             self.cmd.data |= 0b1 << $pos;
             self.cmd
         }
@@ -67,7 +87,7 @@ macro_rules! impl_enable {
 macro_rules! impl_clear_bit {
     ($alias:ident, $ret:ty, $pos:literal) => {
         #[must_use]
-        pub fn $alias(mut self) -> $ret {
+        pub const fn $alias(mut self) -> $ret {
             self.cmd.data &= !(0b1 << $pos);
             self.cmd
         }
@@ -75,7 +95,7 @@ macro_rules! impl_clear_bit {
     ($ret:ty, $pos:literal) => {
         /// Clears the bit field.
         #[must_use]
-        pub fn clear_bit(mut self) -> $ret {
+        pub const fn clear_bit(mut self) -> $ret {
             self.cmd.data &= !(0b1 << $pos);
             self.cmd
         }
@@ -86,7 +106,7 @@ macro_rules! impl_clear_bit {
 macro_rules! impl_disable {
     ($ret:ty, $pos:literal) => {
         #[must_use]
-        pub fn disable(mut self) -> $ret {
+        pub const fn disable(mut self) -> $ret {
             self.cmd.data &= !(0b1 << $pos);
             self.cmd
         }
@@ -143,6 +163,7 @@ mod tests {
     impl_toggle_writer!(BitW, Cmd, 1);
 
     #[test]
+    #[allow(clippy::unusual_byte_groupings)]
     fn macro_tests() {
         let expect = Cmd { data: 0b111_1100 };
         let test = Cmd { data: 0 }.bits_w().bits(0b111_11);
@@ -151,6 +172,20 @@ mod tests {
             "Got {:#b}, expected {:#b}",
             test.data, expect.data
         );
+        let expect = Cmd { data: 0b1000_0011 };
+        let test = Cmd { data: 0b1111_1111 }.bits_w().bits(0);
+        assert_eq!(
+            test, expect,
+            "Got {:#b}, expected {:#b}",
+            test.data, expect.data
+        );
+        let expect = Cmd { data: 0b101_0100 };
+        let test = Cmd { data: 0b111_1100 }.bits_w().bits(0b1_0101);
+        assert_eq!(
+            test, expect,
+            "Got {:#b}, expected {:#b}",
+            test.data, expect.data
+        );
         let expect = Cmd { data: 0b111_1100 };
         let test = Cmd { data: 0 }.bits_w().bits(!0);
         assert_eq!(
@@ -158,6 +193,12 @@ mod tests {
             "Got {:#b}, expected {:#b}",
             test.data, expect.data
         );
+        let expect = Ok(Cmd { data: 0b111_1100 });
+        let test = Cmd { data: 0 }.bits_w().try_bits(0b1_1111);
+        assert_eq!(test, expect);
+        let expect = Err(crate::command::OutOfRange);
+        let test = Cmd { data: 0 }.bits_w().try_bits(0b10_0000);
+        assert_eq!(test, expect);
         let expect = Cmd { data: 0b10 };
         let test = Cmd { data: 0 }.bit_w().bit(true);
         assert_eq!(
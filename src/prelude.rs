@@ -2,13 +2,17 @@ pub use crate::command::active_control::active_control;
 pub use crate::command::analogue_audio_path::{analogue_audio_path, SideAttdB};
 pub use crate::command::digital_audio_interface::digital_audio_interface;
 pub use crate::command::digital_audio_path::digital_audio_path;
-pub use crate::command::headphone_out::{left_headphone_out, right_headphone_out, HpVoldB};
-pub use crate::command::line_in::{left_line_in, right_line_in, InVoldB};
-pub use crate::command::power_down::power_down;
+pub use crate::command::headphone_out::{
+    both_headphone_out, left_headphone_out, right_headphone_out, HpVoldB,
+};
+pub use crate::command::line_in::{both_line_in, left_line_in, right_line_in, InVoldB};
+pub use crate::command::power_down::{power_down, power_down_from};
 pub use crate::command::reset::reset;
 pub use crate::command::sampling::sampling;
 pub use crate::command::sampling::sampling_with_mclk;
 
 pub use crate::command::Command;
 
-pub use crate::interface::{I2CInterface, SPIInterfaceU16, SPIInterfaceU8};
+#[cfg(feature = "async")]
+pub use crate::interface::{AsyncI2CInterface, AsyncSPIInterface};
+pub use crate::interface::{I2CInterface, SPIInterfaceU16, SPIInterfaceU8, Wm8731Address};
@@ -0,0 +1,114 @@
+//! Fixed capacity command queue.
+
+use crate::interface::{Frame, WriteFrame};
+use crate::Wm8731;
+
+///Error returned when pushing into a full queue.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueueFull;
+
+///Queue of up to `N` frames, to stage commands, for example in an interrupt handler, and send
+///them later. It doesn't need an allocator.
+///
+///## Example
+///```
+///# use wm8731_alt::prelude::*;
+///# use wm8731_alt::Wm8731;
+///# use wm8731_alt::interface::WriteFrame;
+///# use wm8731_alt::queue::CommandQueue;
+///# fn flush<I: WriteFrame>(codec: &mut Wm8731<I>) -> Result<(), I::Error> {
+///let mut queue = CommandQueue::<4>::new();
+///queue.push(power_down().outpd().disable()).unwrap();
+///queue.push(active_control().active()).unwrap();
+///queue.flush(codec)
+///# }
+///```
+#[derive(Debug, Clone)]
+pub struct CommandQueue<const N: usize> {
+    frames: [Frame; N],
+    len: usize,
+}
+
+impl<const N: usize> CommandQueue<N> {
+    ///Instantiate an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            frames: [unsafe { Frame::from_parts(0, 0) }; N],
+            len: 0,
+        }
+    }
+    ///Append a frame, fails if the queue is full.
+    pub fn push<F: Into<Frame>>(&mut self, frame: F) -> Result<(), QueueFull> {
+        let slot = self.frames.get_mut(self.len).ok_or(QueueFull)?;
+        *slot = frame.into();
+        self.len += 1;
+        Ok(())
+    }
+    ///Return queued frames, in pushing order.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames[..self.len]
+    }
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    ///Remove every queued frame.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+    ///Send queued frames in pushing order, stopping at the first error.
+    ///
+    ///Sent frames are removed from the queue, so on error the queue holds the frame that failed
+    ///followed by those not sent yet.
+    pub fn flush<I: WriteFrame>(&mut self, codec: &mut Wm8731<I>) -> Result<(), I::Error> {
+        let mut sent = 0;
+        let mut res = Ok(());
+        for &frame in self.frames() {
+            res = codec.send(frame);
+            if res.is_err() {
+                break;
+            }
+            sent += 1;
+        }
+        self.frames.copy_within(sent..self.len, 0);
+        self.len -= sent;
+        res
+    }
+}
+
+impl<const N: usize> Default for CommandQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    use crate::interface::RecordingInterface;
+
+    #[test]
+    fn push_and_flush() {
+        let mut queue = CommandQueue::<2>::new();
+        queue.push(power_down().outpd().disable()).unwrap();
+        queue.push(active_control().active()).unwrap();
+        let res = queue.push(reset());
+        assert!(
+            res == Err(QueueFull),
+            "Got {:?}, expected {:?}",
+            res,
+            QueueFull
+        );
+        let mut wm8731 = Wm8731::new_without_reset(RecordingInterface::new());
+        queue.flush(&mut wm8731).unwrap();
+        assert!(queue.is_empty(), "Got {} frames, expected 0", queue.len());
+        let interface = wm8731.release();
+        let test = interface.frames();
+        let expected = [0b110 << 9 | 0b1000_1111, 0b1001 << 9 | 0b1];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+}
@@ -0,0 +1,107 @@
+//! Register addresses.
+//!
+//! A control word is the register address shifted left by 9 bits, followed by the 9 bits register
+//! content.
+
+///Left line in register.
+pub const LEFT_LINE_IN: u8 = 0;
+///Right line in register.
+pub const RIGHT_LINE_IN: u8 = 1;
+///Left headphone out register.
+pub const LEFT_HEADPHONE_OUT: u8 = 2;
+///Right headphone out register.
+pub const RIGHT_HEADPHONE_OUT: u8 = 3;
+///Analogue audio path register.
+pub const ANALOGUE_AUDIO_PATH: u8 = 4;
+///Digital audio path register.
+pub const DIGITAL_AUDIO_PATH: u8 = 5;
+///Power down register.
+pub const POWER_DOWN: u8 = 6;
+///Digital audio interface register.
+pub const DIGITAL_AUDIO_INTERFACE: u8 = 7;
+///Sampling register.
+pub const SAMPLING: u8 = 8;
+///Active control register.
+pub const ACTIVE_CONTROL: u8 = 9;
+///Reset register.
+pub const RESET: u8 = 15;
+
+///BOTH bit of line in and headphone out registers, loading the written content into both
+///channels.
+pub const BOTH: u16 = 1 << 8;
+
+///Return the register also loaded by writing `value` to `addr`, that is the other channel when
+///`addr` is a line in or headphone out register and the BOTH bit is set.
+///
+///The other channel receives every field of `value` but the BOTH bit, its own BOTH bit is kept.
+pub const fn both_target(addr: u8, value: u16) -> Option<u8> {
+    if addr <= RIGHT_HEADPHONE_OUT && value & BOTH != 0 {
+        Some(addr ^ 1)
+    } else {
+        None
+    }
+}
+
+///Left and right line in registers content after a reset.
+pub const LINE_IN_DEFAULT: u16 = 0b0_1001_0111;
+///Left and right headphone out registers content after a reset.
+pub const HEADPHONE_OUT_DEFAULT: u16 = 0b0_0111_1001;
+///Analogue audio path register content after a reset.
+pub const ANALOGUE_AUDIO_PATH_DEFAULT: u16 = 0b0_0000_1010;
+///Digital audio path register content after a reset.
+pub const DIGITAL_AUDIO_PATH_DEFAULT: u16 = 0b0_0000_1000;
+///Power down register content after a reset.
+pub const POWER_DOWN_DEFAULT: u16 = 0b0_1001_1111;
+///Digital audio interface register content after a reset.
+pub const DIGITAL_AUDIO_INTERFACE_DEFAULT: u16 = 0b0_0000_1010;
+///Sampling register content after a reset.
+pub const SAMPLING_DEFAULT: u16 = 0;
+///Active control register content after a reset.
+pub const ACTIVE_CONTROL_DEFAULT: u16 = 0;
+
+///Registers content after a reset, indexed by register address.
+///
+///Values are the 9 bits register content, without the address.
+pub const DEFAULTS: [u16; 10] = [
+    LINE_IN_DEFAULT,
+    LINE_IN_DEFAULT,
+    HEADPHONE_OUT_DEFAULT,
+    HEADPHONE_OUT_DEFAULT,
+    ANALOGUE_AUDIO_PATH_DEFAULT,
+    DIGITAL_AUDIO_PATH_DEFAULT,
+    POWER_DOWN_DEFAULT,
+    DIGITAL_AUDIO_INTERFACE_DEFAULT,
+    SAMPLING_DEFAULT,
+    ACTIVE_CONTROL_DEFAULT,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::*;
+    #[test]
+    fn defaults_match_builders() {
+        let test = [
+            left_line_in().into_command(),
+            right_line_in().into_command(),
+            left_headphone_out().into_command(),
+            right_headphone_out().into_command(),
+            analogue_audio_path().into_command(),
+            digital_audio_path().into_command(),
+            power_down().into_command(),
+            digital_audio_interface().into_command(),
+            sampling().into_command(),
+            active_control().into_command(),
+        ]
+        .map(|cmd| cmd.value());
+        assert!(test == DEFAULTS, "Got {:?}, expected {:?}", test, DEFAULTS);
+    }
+    #[test]
+    fn both_target_channels() {
+        let test = [0, 1, 2, 3, 4].map(|addr| both_target(addr, BOTH));
+        let expected = [Some(1), Some(0), Some(3), Some(2), None];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+        let test = both_target(LEFT_LINE_IN, LINE_IN_DEFAULT);
+        assert!(test.is_none(), "Got {:?}, expected None", test);
+    }
+}
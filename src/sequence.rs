@@ -0,0 +1,278 @@
+//! Canned command sequences.
+
+use crate::command::digital_audio_path::DigitalAudioPath;
+use crate::command::power_down::PowerDown;
+use crate::command::sampling::Mclk12M288;
+use crate::command::*;
+use crate::interface::Frame;
+
+///Accumulate commands into a fixed-size array of frames, to store a whole configuration as one
+///value.
+///
+///`N` is the number of commands of the configuration, usually inferred from the type of the
+///built array. The array can be stored, for example in a struct field, and replayed with
+///[`Wm8731::send_all`](crate::Wm8731::send_all).
+///
+///## Example
+///```
+///# use wm8731_alt::prelude::*;
+///# use wm8731_alt::Wm8731;
+///# use wm8731_alt::interface::{Frame, WriteFrame};
+///# use wm8731_alt::sequence::ConfigBuilder;
+///# fn init<I: WriteFrame>(codec: &mut Wm8731<I>) -> Result<(), I::Error> {
+///let cfg: [Frame; 2] = ConfigBuilder::new()
+///    .with(power_down().poweroff().disable())
+///    .with(active_control().active())
+///    .build();
+///codec.send_all(cfg)?;
+///codec.send_all(cfg)
+///# }
+///```
+#[derive(Debug, Copy, Clone)]
+pub struct ConfigBuilder<const N: usize> {
+    frames: [Frame; N],
+    len: usize,
+}
+
+impl<const N: usize> ConfigBuilder<N> {
+    ///Instantiate an empty configuration.
+    pub fn new() -> Self {
+        Self {
+            frames: [reset().into(); N],
+            len: 0,
+        }
+    }
+    ///Append a command to the configuration.
+    ///
+    ///# Panics
+    ///
+    ///Panics if `N` commands were already appended.
+    #[must_use]
+    pub fn with<F: Into<Frame>>(mut self, cmd: F) -> Self {
+        assert!(
+            self.len < N,
+            "more than {} commands in the configuration",
+            N
+        );
+        self.frames[self.len] = cmd.into();
+        self.len += 1;
+        self
+    }
+    ///Return frames in the order commands were appended.
+    ///
+    ///# Panics
+    ///
+    ///Panics if less than `N` commands were appended.
+    pub fn build(self) -> [Frame; N] {
+        assert!(
+            self.len == N,
+            "{} commands in the configuration, expected {}",
+            self.len,
+            N
+        );
+        self.frames
+    }
+}
+
+impl<const N: usize> Default for ConfigBuilder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///Default initialization sequence.
+///
+///Configure the codec for 48kHz sampling from a 12.288MHz master clock, with a 16 bits I2S slave
+///digital audio interface, line input to ADC and DAC to headphone output. It's meant to be sent
+///right after a reset, see [`Wm8731::init_default`](crate::Wm8731::init_default).
+///
+///Registers are written in this order:
+/// 1. Power down: everything powered up except microphone and outputs.
+/// 2. Left line in, with `inboth` set: both line inputs unmuted at 0dB.
+/// 3. Left headphone out, with `hpboth` set: both headphone outputs at 0dB.
+/// 4. Analogue audio path: DAC selected, bypass disabled, line input to ADC.
+/// 5. Digital audio path: DAC unmuted.
+/// 6. Digital audio interface: I2S format, 16 bits, slave mode.
+/// 7. Sampling: 48kHz ADC and DAC from a 12.288MHz master clock.
+/// 8. Active control: digital audio interface activated.
+/// 9. Power down: outputs powered up.
+///
+///Other fields are left to their reset values, use individual builders afterward to customize the
+///configuration.
+pub fn init_default() -> [Frame; 9] {
+    let power = power_down()
+        .poweroff()
+        .disable()
+        .lineinpd()
+        .disable()
+        .adcpd()
+        .disable()
+        .dacpd()
+        .disable();
+    [
+        power.into_command().into(),
+        left_line_in()
+            .inmute()
+            .disable()
+            .inboth()
+            .enable()
+            .into_command()
+            .into(),
+        left_headphone_out().hpboth().enable().into_command().into(),
+        analogue_audio_path()
+            .dacsel()
+            .select()
+            .bypass()
+            .disable()
+            .into_command()
+            .into(),
+        digital_audio_path().dacmu().disable().into_command().into(),
+        digital_audio_interface()
+            .format()
+            .i2s()
+            .iwl()
+            .iwl_16_bits()
+            .ms()
+            .slave()
+            .into_command()
+            .into(),
+        sampling_with_mclk(Mclk12M288)
+            .sample_rate()
+            .adc48k_dac48k()
+            .into_command()
+            .into(),
+        active_control().active().into_command().into(),
+        power.outpd().disable().into_command().into(),
+    ]
+}
+
+///Time left to the VMID reference to charge, in microseconds, by
+///[`Wm8731::anti_pop_startup`](crate::Wm8731::anti_pop_startup).
+///
+///The charge time grows with the VMID decoupling capacitor, this value is a conservative one for
+///usual capacitors.
+pub const VMID_SETTLING_US: u32 = 500_000;
+
+///Anti-pop power up sequence.
+///
+///Registers are written in this order:
+/// 1. Power down: `poweroff` cleared, every block still powered down. It must be cleared before
+///    any other block can power up.
+/// 2. Power down: every block powered up except outputs, see [`PowerDown::power_up_analog`].
+/// 3. Digital audio path: DAC unmuted, other fields taken from `path`.
+/// 4. Power down: outputs powered up.
+///
+///`path` is the current content of the digital audio path register, so de-emphasis and filter
+///settings are kept, see [`Wm8731::power_up_sequence`](crate::Wm8731::power_up_sequence).
+pub fn power_up(path: DigitalAudioPath) -> [Frame; 4] {
+    let power = PowerDown::power_up_analog();
+    [
+        power_down().poweroff().disable().into_command().into(),
+        power.into_command().into(),
+        path.dacmu().disable().into_command().into(),
+        power.outpd().disable().into_command().into(),
+    ]
+}
+
+///Step of a timed sequence, see [`Wm8731::send_sequenced`](crate::Wm8731::send_sequenced).
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Step {
+    ///Send a frame.
+    Cmd(Frame),
+    ///Wait for the given time, in microseconds.
+    Delay(u32),
+}
+
+impl From<Frame> for Step {
+    fn from(frame: Frame) -> Step {
+        Step::Cmd(frame)
+    }
+}
+
+///Anti-pop power up sequence, with the wait for VMID to charge.
+///
+///Frames are the ones of [`power_up`], with a [`VMID_SETTLING_US`] delay after powering up
+///analog blocks.
+pub fn timed_power_up(path: DigitalAudioPath) -> [Step; 5] {
+    let [poweron, analog, unmute, outputs] = power_up(path);
+    [
+        Step::Cmd(poweron),
+        Step::Cmd(analog),
+        Step::Delay(VMID_SETTLING_US),
+        Step::Cmd(unmute),
+        Step::Cmd(outputs),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn init_default_order() {
+        let seq = init_default();
+        let addresses = seq.map(|frame| u16::from(frame) >> 9);
+        let expected = [6, 0, 2, 4, 5, 7, 8, 9, 6];
+        assert!(
+            addresses == expected,
+            "Got {:?}, expected {:?}",
+            addresses,
+            expected
+        );
+        let last = u16::from(seq[8]);
+        let expected = 0b110 << 9 | 0b0000_0010;
+        assert!(
+            last == expected,
+            "Got {:#b}, expected {:#b}",
+            last,
+            expected
+        );
+    }
+    #[test]
+    fn power_up_order() {
+        let path = digital_audio_path().adchpd().enable().hpor().store_offset();
+        let seq = power_up(path).map(u16::from);
+        let expected = [
+            0b110 << 9 | 0b0001_1111,
+            0b110 << 9 | 0b0001_0000,
+            0b101 << 9 | 0b1_0001,
+            0b110 << 9,
+        ];
+        assert!(seq == expected, "Got {:?}, expected {:?}", seq, expected);
+    }
+    #[test]
+    fn timed_power_up_order() {
+        let seq = timed_power_up(digital_audio_path());
+        let [poweron, analog, unmute, outputs] = power_up(digital_audio_path());
+        let expected = [
+            Step::Cmd(poweron),
+            Step::Cmd(analog),
+            Step::Delay(VMID_SETTLING_US),
+            Step::Cmd(unmute),
+            Step::Cmd(outputs),
+        ];
+        assert!(seq == expected, "Got {:?}, expected {:?}", seq, expected);
+    }
+    #[test]
+    fn config_builder() {
+        let cfg: [Frame; 2] = ConfigBuilder::new()
+            .with(power_down().poweroff().disable())
+            .with(active_control().active().into_command())
+            .build();
+        let test = cfg.map(u16::from);
+        let expected = [0b110 << 9 | 0b0001_1111, 0b1001 << 9 | 0b1];
+        assert!(test == expected, "Got {:?}, expected {:?}", test, expected);
+    }
+    #[test]
+    #[should_panic]
+    fn config_builder_too_many() {
+        let _ = ConfigBuilder::<1>::new()
+            .with(power_down())
+            .with(active_control().active());
+    }
+    #[test]
+    #[should_panic]
+    fn config_builder_missing() {
+        let _: [Frame; 2] = ConfigBuilder::new().with(power_down()).build();
+    }
+}
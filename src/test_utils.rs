@@ -0,0 +1,43 @@
+//! Helpers shared by unit tests.
+
+///Drive a future to completion, for futures never returning `Pending`, like the ones of fake
+///interfaces.
+#[cfg(feature = "async")]
+pub fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    fn noop_raw() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw()) };
+    let mut future = core::pin::pin!(future);
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+///Delay returning immediately, recording requested durations in order.
+#[derive(Debug, Default)]
+pub struct FakeDelay {
+    pub delays: std::vec::Vec<u32>,
+}
+
+impl FakeDelay {
+    ///Return the sum of requested durations.
+    pub fn total(&self) -> u32 {
+        self.delays.iter().sum()
+    }
+}
+
+impl embedded_hal::blocking::delay::DelayUs<u32> for FakeDelay {
+    fn delay_us(&mut self, us: u32) {
+        self.delays.push(us);
+    }
+}